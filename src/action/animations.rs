@@ -1,29 +1,34 @@
 use super::INDEX;
 use crate::components::Animation;
+use crate::core::Status;
 use crate::crust::{AnimationScriptAction, SceneNodeRefAction};
 use specs::prelude::*;
 
 pub struct Animations;
 
 impl Animations {
-    pub fn play(script_action: AnimationScriptAction, world: &mut World) {
-        if let Some(script) = script_action.script {
+    pub fn play(script_action: &AnimationScriptAction, world: &mut World) -> Result<(), Status> {
+        if let Some(script) = &script_action.script {
             let mut entity_id = None;
             INDEX.with(|index| {
                 if let Some(index) = &*index.borrow() {
                     entity_id = index.find_entity(&script_action.scene_node_id);
                 }
             });
+            let entity_id = entity_id.ok_or_else(|| {
+                Status::not_found(format!(
+                    "play_animation(): scene node '{}' not found",
+                    &script_action.scene_node_id
+                ))
+            })?;
 
-            if let Some(id) = entity_id {
-                let entity = world.entities().entity(id);
-
-                let mut scripts = world.write_storage::<Animation>();
-                if let Err(e) = scripts.insert(entity, Animation::new(script)) {
-                    eprintln!("play_animation(): {}", e);
-                }
-            }
+            let entity = world.entities().entity(entity_id);
+            let mut scripts = world.write_storage::<Animation>();
+            scripts
+                .insert(entity, Animation::new(script.clone()))
+                .map_err(|e| Status::new("play_animation()", e))?;
         }
+        Ok(())
     }
 
     pub fn stop(scene_node_ref_action: SceneNodeRefAction, world: &mut World) {
@@ -1,37 +1,44 @@
 use super::INDEX;
-use crate::{components, crust::CollisionAction};
+use crate::{components, core::Status, crust::CollisionAction};
 use specs::prelude::*;
 
 pub struct Collisions;
 
 impl Collisions {
-    pub fn on_collision(collision_action: CollisionAction, world: &mut World) {
+    pub fn on_collision(
+        collision_action: &CollisionAction,
+        world: &mut World,
+    ) -> Result<(), Status> {
         let mut entity_id = None;
         INDEX.with(|index| {
             if let Some(index) = &*index.borrow() {
                 entity_id = index.find_entity(&collision_action.scene_node_id);
             }
         });
+        let entity_id = entity_id.ok_or_else(|| {
+            Status::not_found(format!(
+                "on_collision(): scene node '{}' not found",
+                &collision_action.scene_node_id
+            ))
+        })?;
 
-        if let Some(id) = entity_id {
-            let entity = world.entities().entity(id);
-
-            let mut collisions = world.write_storage::<components::Collisions>();
-            match collisions.get_mut(entity) {
-                Some(collisions) => {
-                    collisions.on_collision.push(collision_action);
-                }
-                None => {
-                    if let Err(e) = collisions.insert(
+        let entity = world.entities().entity(entity_id);
+        let mut collisions = world.write_storage::<components::Collisions>();
+        match collisions.get_mut(entity) {
+            Some(collisions) => {
+                collisions.on_collision.push(collision_action.clone());
+            }
+            None => {
+                collisions
+                    .insert(
                         entity,
                         components::Collisions {
-                            on_collision: vec![collision_action],
+                            on_collision: vec![collision_action.clone()],
                         },
-                    ) {
-                        eprintln!("on_collision(): {}", e);
-                    }
-                }
+                    )
+                    .map_err(|e| Status::new("on_collision()", e))?;
             }
         }
+        Ok(())
     }
 }
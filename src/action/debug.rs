@@ -0,0 +1,10 @@
+use crate::crust::DebugAction;
+use specs::prelude::*;
+
+pub struct Debug;
+
+impl Debug {
+    pub fn set_debug_info(debug_action: DebugAction, world: &mut World) {
+        *world.write_resource() = debug_action.debug.unwrap_or_default();
+    }
+}
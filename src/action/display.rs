@@ -0,0 +1,46 @@
+use crate::{
+    core::Status,
+    crust::{FullscreenAction, FullscreenMode, ResizeWindowAction},
+    resources::{PendingWindowChanges, WindowChange},
+};
+use specs::prelude::*;
+
+pub struct Display;
+
+impl Display {
+    pub fn set_fullscreen(
+        fullscreen_action: &FullscreenAction,
+        world: &mut World,
+    ) -> Result<(), Status> {
+        let mode = FullscreenMode::from_i32(fullscreen_action.mode).ok_or_else(|| {
+            Status::invalid_argument(format!(
+                "set_fullscreen(): unknown mode {}",
+                fullscreen_action.mode
+            ))
+        })?;
+
+        world
+            .write_resource::<PendingWindowChanges>()
+            .0
+            .push(WindowChange::Fullscreen(mode));
+        Ok(())
+    }
+
+    pub fn resize(resize_action: &ResizeWindowAction, world: &mut World) -> Result<(), Status> {
+        if resize_action.width <= 0 || resize_action.height <= 0 {
+            return Err(Status::invalid_argument(format!(
+                "resize_window(): invalid size {:?}",
+                resize_action
+            )));
+        }
+
+        world
+            .write_resource::<PendingWindowChanges>()
+            .0
+            .push(WindowChange::Resize(
+                resize_action.width as u32,
+                resize_action.height as u32,
+            ));
+        Ok(())
+    }
+}
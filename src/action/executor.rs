@@ -1,12 +1,16 @@
 use super::{
-    animations::Animations, collisions::Collisions, events::Events, nodes::Nodes, scenes::Scenes,
-    scrolling::Scrolling,
+    animations::Animations, collisions::Collisions, debug::Debug, display::Display, events::Events,
+    nodes::Nodes, particles::Particles, resources::Resources, scenes::Scenes,
+    screenshots::Screenshots, scrolling::Scrolling, shapes::Shapes, time::Time,
 };
 use crate::{
-    crust::{action, Action},
+    core::Status,
+    crust::{action, event, Action, ErrorEvent, Event},
     event::EventManager,
     scene::SceneManager,
 };
+use log::error;
+use prost::Message;
 use specs::prelude::*;
 use std::sync::mpsc::Receiver;
 
@@ -14,6 +18,7 @@ pub struct ActionExecutor {
     rx: Receiver<Action>,
 
     scrolling: Scrolling,
+    quit: bool,
 }
 
 impl ActionExecutor {
@@ -21,38 +26,186 @@ impl ActionExecutor {
         ActionExecutor {
             rx,
             scrolling: Scrolling::new(world),
+            quit: false,
         }
     }
 
-    pub fn process(
-        &self,
-        world: &mut World,
-        scene_manager: &mut SceneManager,
-        event_manager: &mut EventManager,
-    ) {
-        self.rx
-            .try_iter()
-            .for_each(|action| self.execute(action, world, scene_manager, event_manager));
+    /// Returns true if a QuitAction was received.
+    pub fn quit_requested(&self) -> bool {
+        self.quit
+    }
+
+    /// Returns the next received Action that is pending execution.
+    pub fn next_action(&mut self) -> Option<Action> {
+        self.rx.try_recv().ok()
     }
 
-    fn execute(
-        &self,
-        action: Action,
+    /// Executes `action`. Failures are reported to the host as an ErrorEvent.
+    pub fn execute(
+        &mut self,
+        mut action: Action,
         world: &mut World,
         scene_manager: &mut SceneManager,
         event_manager: &mut EventManager,
     ) {
-        match action.action {
-            Some(action::Action::Quit(..)) => (),
+        // Actions that can fail are borrowed, so that they can be reported
+        // with the error. The others are taken.
+        let result = match &mut action.action {
+            Some(action::Action::Quit(..)) => {
+                self.quit = true;
+                Ok(())
+            }
             Some(action::Action::LoadScene(action)) => Scenes::load(action, scene_manager, world),
             Some(action::Action::CreateSceneNode(action)) => Nodes::create(action, world),
             Some(action::Action::DestroySceneNode(action)) => Nodes::destroy(action, world),
             Some(action::Action::PlayAnimation(action)) => Animations::play(action, world),
-            Some(action::Action::StopAnimation(action)) => Animations::stop(action, world),
-            Some(action::Action::Scroll(action)) => self.scrolling.scroll(action, world),
+            Some(action::Action::StopAnimation(action)) => {
+                Animations::stop(std::mem::take(action), world);
+                Ok(())
+            }
+            Some(action::Action::Scroll(action)) => {
+                self.scrolling.scroll(std::mem::take(action), world);
+                Ok(())
+            }
             Some(action::Action::OnCollision(action)) => Collisions::on_collision(action, world),
-            Some(action::Action::Emit(action)) => Events::emit(action, event_manager),
-            _ => (),
+            Some(action::Action::Emit(action)) => {
+                Events::emit(std::mem::take(action), event_manager);
+                Ok(())
+            }
+            Some(action::Action::SetLayerVisibility(action)) => {
+                Scenes::set_layer_visibility(action, scene_manager)
+            }
+            Some(action::Action::SetLayerOpacity(action)) => {
+                Scenes::set_layer_opacity(action, scene_manager)
+            }
+            Some(action::Action::Screenshot(action)) => Screenshots::take(action, world),
+            Some(action::Action::SetFullscreen(action)) => Display::set_fullscreen(action, world),
+            Some(action::Action::ResizeWindow(action)) => Display::resize(action, world),
+            Some(action::Action::SetDebugInfo(action)) => {
+                Debug::set_debug_info(std::mem::take(action), world);
+                Ok(())
+            }
+            Some(action::Action::StartEmitter(action)) => Particles::start(action, world),
+            Some(action::Action::StopEmitter(action)) => {
+                Particles::stop(std::mem::take(action), world);
+                Ok(())
+            }
+            Some(action::Action::SetShape(action)) => Shapes::set(action, world),
+            Some(action::Action::PauseGame(action)) => {
+                Time::pause(std::mem::take(action), world);
+                Ok(())
+            }
+            Some(action::Action::ResumeGame(action)) => {
+                Time::resume(std::mem::take(action), world);
+                Ok(())
+            }
+            Some(action::Action::SetTimeScale(action)) => Time::set_scale(action, world),
+            Some(action::Action::UnloadScene(..)) => Scenes::unload(scene_manager, world),
+            Some(action::Action::QueryResourceStats(action)) => {
+                Resources::stats(std::mem::take(action), scene_manager, event_manager, world);
+                Ok(())
+            }
+            Some(action::Action::Preload(action)) => {
+                Resources::preload(std::mem::take(action), world);
+                Ok(())
+            }
+            Some(action::Action::QueryProperties(action)) => {
+                Nodes::query_properties(action, event_manager, world)
+            }
+            Some(action::Action::TilePosition(action)) => {
+                Scenes::tile_position(action, scene_manager, event_manager)
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(status) = result {
+            error!("{status}");
+            event_manager.handle(error_event(&action, status));
+        }
+    }
+}
+
+fn error_event(action: &Action, status: Status) -> Event {
+    Event {
+        event_id: String::from("error"),
+        event: Some(event::Event::OnError(ErrorEvent {
+            action: action.encode_to_vec(),
+            code: status.code() as i32,
+            message: status.message().to_owned(),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::{Index, INDEX},
+        components::ScrollingInfo,
+        crust::{
+            AnimationScript, AnimationScriptAction, CollisionAction, SceneNodeRefAction, Shape,
+            ShapeAction, StatusCode, TimeScaleAction,
+        },
+        resources::testing::MemorySource,
+    };
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{mpsc, Arc},
+    };
+
+    #[test]
+    fn unknown_scene_nodes_are_reported() {
+        INDEX.with(|index| *index.borrow_mut() = Some(Index::new()));
+        let mut world = World::new();
+        world.register::<ScrollingInfo>();
+        let (_tx, rx) = mpsc::channel();
+        let mut executor = ActionExecutor::new(rx, &mut world);
+        let mut scene_manager = SceneManager::new(Arc::new(MemorySource::default()));
+
+        let errors = Rc::new(RefCell::new(vec![]));
+        let mut event_manager = EventManager::new();
+        let handler_errors = Rc::clone(&errors);
+        event_manager.register(Box::new(move |event| {
+            if let Some(event::Event::OnError(error)) = &event.event {
+                handler_errors.borrow_mut().push(error.clone());
+            }
+        }));
+
+        let actions = [
+            action::Action::PlayAnimation(AnimationScriptAction {
+                script: Some(AnimationScript::default()),
+                scene_node_id: "ghost".to_owned(),
+                ..Default::default()
+            }),
+            action::Action::OnCollision(CollisionAction {
+                scene_node_id: "ghost".to_owned(),
+                ..Default::default()
+            }),
+            action::Action::DestroySceneNode(SceneNodeRefAction {
+                scene_node_id: "ghost".to_owned(),
+            }),
+            action::Action::SetTimeScale(TimeScaleAction {
+                scene_node_id: "ghost".to_owned(),
+                scale: 1.0,
+            }),
+            action::Action::SetShape(ShapeAction {
+                scene_node_id: "ghost".to_owned(),
+                shape: Some(Shape::default()),
+            }),
+        ]
+        .map(|action| Action {
+            action: Some(action),
+        });
+        for action in actions.iter().cloned() {
+            executor.execute(action, &mut world, &mut scene_manager, &mut event_manager);
+        }
+
+        let errors = errors.borrow();
+        assert_eq!(errors.len(), actions.len());
+        for (error, action) in errors.iter().zip(&actions) {
+            assert_eq!(error.code, StatusCode::NotFound as i32);
+            assert_eq!(&Action::decode(&error.action[..]).unwrap(), action);
         }
     }
 }
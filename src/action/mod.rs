@@ -1,12 +1,19 @@
 mod animations;
 mod collisions;
+mod debug;
+mod display;
 mod events;
 mod executor;
 mod index;
 mod nodes;
+mod particles;
 mod queue;
+mod resources;
 mod scenes;
+mod screenshots;
 mod scrolling;
+mod shapes;
+mod time;
 
 pub use executor::ActionExecutor;
 pub use index::{Index, INDEX};
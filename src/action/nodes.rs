@@ -1,49 +1,76 @@
 use super::INDEX;
-use crate::components::{Id, Position, RigidBody, Rotation, Scaling, SpriteInfo, Velocity};
-use crate::crust::{SceneNodeAction, SceneNodeRefAction, Vector};
-use crate::resources::SpriteManager;
+use crate::components::{
+    Depth, Id, Position, Properties, RigidBody, Rotation, Scaling, ScreenSpace, ShapeInfo,
+    SpriteInfo, Velocity,
+};
+use crate::core::Status;
+use crate::crust::{
+    event, Anchor, Event, PropertiesEvent, QueryPropertiesAction, SceneNodeAction,
+    SceneNodeRefAction, Vector,
+};
+use crate::event::EventManager;
+use crate::resources::{SpriteManager, TileFlip};
 use sdl2::rect::{Point, Rect};
 use specs::prelude::*;
 
 pub struct Nodes;
 
 impl Nodes {
-    pub fn create(node_action: SceneNodeAction, world: &mut World) {
-        if let Some(node) = node_action.scene_node {
-            let bbox = match frame_bounding_box(world, &node.sprite_id, node.frame_index as usize) {
-                Some(bbox) => bbox,
-                None => {
-                    eprintln!(
-                        "🦀 Failed to retrieve frame '{}' from resouce sheet '{}'",
-                        node.frame_index, &node.sprite_id
-                    );
-                    return;
-                }
+    pub fn create(node_action: &SceneNodeAction, world: &mut World) -> Result<(), Status> {
+        if let Some(node) = &node_action.scene_node {
+            let shape_info = node.shape.clone().map(|shape| ShapeInfo { shape });
+            let bbox = match &shape_info {
+                Some(shape_info) => shape_info.bounding_box(),
+                None => frame_bounding_box(world, &node.sprite_id, node.frame_index as usize)?
+                    .ok_or_else(|| {
+                        Status::not_found(format!(
+                            "Failed to retrieve frame '{}' from resouce sheet '{}'",
+                            node.frame_index, &node.sprite_id
+                        ))
+                    })?,
             };
 
             let mut position = bbox;
-            position.reposition(make_point(
-                &node
-                    .position
-                    .expect(&format!("Node '{}' missing position", &node.id)),
-            ));
+            position.reposition(make_point(node.position.as_ref().ok_or_else(|| {
+                Status::invalid_argument(format!("Node '{}' missing position", &node.id))
+            })?));
+
+            let sprite_ref = match &shape_info {
+                Some(_) => None,
+                None => world
+                    .read_resource::<SpriteManager>()
+                    .acquire(node.sprite_id.as_str()),
+            };
 
             let mut builder = world
                 .create_entity()
                 .with(Id(node.id.clone()))
                 .with(Position(position))
+                .with(Depth(node.depth))
                 .with(Velocity::default())
                 .with(Rotation::default())
-                .with(Scaling::default())
-                .with(SpriteInfo {
+                .with(Scaling::default());
+
+            builder = match shape_info {
+                Some(shape_info) => builder.with(shape_info),
+                None => builder.with(SpriteInfo {
+                    sprite_id: node.sprite_id.clone(),
                     texture_id: node.sprite_id.clone(),
                     frame_index: node.frame_index as usize,
                     bounding_box: bbox,
-                });
+                    flip: TileFlip::default(),
+                    sprite_ref,
+                }),
+            };
 
             if node.rigid_body {
                 builder = builder.with(RigidBody {});
             }
+            if node.screen_space {
+                builder = builder.with(ScreenSpace {
+                    anchor: Anchor::from_i32(node.anchor).unwrap_or(Anchor::TopLeft),
+                });
+            }
             let entity = builder.build();
 
             INDEX.with(|index| {
@@ -52,33 +79,75 @@ impl Nodes {
                 }
             });
         }
+        Ok(())
     }
 
-    pub fn destroy(node_ref_action: SceneNodeRefAction, world: &mut World) {
+    pub fn destroy(node_ref_action: &SceneNodeRefAction, world: &mut World) -> Result<(), Status> {
         let mut entity_id = None;
         INDEX.with(|index| {
             if let Some(index) = &mut *index.borrow_mut() {
                 entity_id = index.remove_entity(&node_ref_action.scene_node_id);
             }
         });
+        let entity_id = entity_id.ok_or_else(|| {
+            Status::not_found(format!(
+                "destroy_scene_node(): scene node '{}' not found",
+                &node_ref_action.scene_node_id
+            ))
+        })?;
+
+        let entity = world.entities().entity(entity_id);
+        world
+            .delete_entity(entity)
+            .map_err(|e| Status::new("destroy_scene_node()", e))?;
+        Ok(())
+    }
 
-        if let Some(id) = entity_id {
-            let entity = world.entities().entity(id);
-            if let Err(e) = world.delete_entity(entity) {
-                eprintln!("🦀 destroy_scene_node(): {}", e);
+    /// Emits the custom properties of a scene node. Nodes that were not
+    /// created from map objects have no properties.
+    pub fn query_properties(
+        query_action: &QueryPropertiesAction,
+        event_manager: &mut EventManager,
+        world: &World,
+    ) -> Result<(), Status> {
+        let mut entity_id = None;
+        INDEX.with(|index| {
+            if let Some(index) = &*index.borrow() {
+                entity_id = index.find_entity(&query_action.scene_node_id);
             }
-        }
+        });
+        let entity_id = entity_id.ok_or_else(|| {
+            Status::not_found(format!(
+                "Scene node '{}' not found",
+                query_action.scene_node_id
+            ))
+        })?;
+
+        let entity = world.entities().entity(entity_id);
+        let properties = world
+            .read_storage::<Properties>()
+            .get(entity)
+            .map(|properties| properties.0.clone())
+            .unwrap_or_default();
+        event_manager.handle(Event {
+            event_id: query_action.event_id.clone(),
+            event: Some(event::Event::NodeProperties(PropertiesEvent {
+                scene_node_id: query_action.scene_node_id.clone(),
+                properties,
+            })),
+        });
+        Ok(())
     }
 }
 
-fn frame_bounding_box(world: &mut World, resource: &str, frame_index: usize) -> Option<Rect> {
+fn frame_bounding_box(
+    world: &mut World,
+    resource: &str,
+    frame_index: usize,
+) -> Result<Option<Rect>, Status> {
     let mut sprite_manager = world.write_resource::<SpriteManager>();
-    if let Err(e) = sprite_manager.load(resource) {
-        eprintln!("🦀 {}", e);
-        return None;
-    }
-
-    sprite_manager.get_box(resource, frame_index)
+    sprite_manager.load(resource)?;
+    Ok(sprite_manager.get_box(resource, frame_index))
 }
 
 fn make_point(vec: &Vector) -> Point {
@@ -0,0 +1,124 @@
+use super::INDEX;
+use crate::{
+    components::ParticleEmitters,
+    core::Status,
+    crust::{EmitterAction, EmitterRefAction},
+    particles::Emitter,
+    resources::SpriteManager,
+};
+use specs::prelude::*;
+
+pub struct Particles;
+
+impl Particles {
+    pub fn start(emitter_action: &EmitterAction, world: &mut World) -> Result<(), Status> {
+        let config = match &emitter_action.emitter {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+
+        let mut entity_id = None;
+        INDEX.with(|index| {
+            if let Some(index) = &*index.borrow() {
+                entity_id = index.find_entity(&emitter_action.scene_node_id);
+            }
+        });
+        let entity_id = entity_id.ok_or_else(|| {
+            Status::not_found(format!(
+                "start_emitter(): scene node '{}' not found",
+                &emitter_action.scene_node_id
+            ))
+        })?;
+
+        let (texture_id, frame) = {
+            let mut sprite_manager = world.write_resource::<SpriteManager>();
+            let texture_id = sprite_manager.load(&config.sprite_id)?.texture_id.clone();
+            let frame = sprite_manager
+                .get_box(&config.sprite_id, config.frame_index as usize)
+                .ok_or_else(|| {
+                    Status::not_found(format!(
+                        "start_emitter(): Failed to retrieve frame '{}' from resouce sheet '{}'",
+                        config.frame_index, &config.sprite_id
+                    ))
+                })?;
+            (texture_id, frame)
+        };
+
+        // Seeding from the ids keeps emitters reproducible across runs.
+        let seed = fnv_hash(&[&emitter_action.scene_node_id, &emitter_action.emitter_id]);
+        let emitter = Emitter::new(
+            emitter_action.emitter_id.clone(),
+            config.clone(),
+            texture_id,
+            frame,
+            seed,
+        );
+
+        let entity = world.entities().entity(entity_id);
+        let mut emitters = world.write_storage::<ParticleEmitters>();
+        match emitters.get_mut(entity) {
+            Some(emitters) => {
+                emitters.emitters.retain(|e| e.id != emitter.id);
+                emitters.emitters.push(emitter);
+            }
+            None => {
+                emitters
+                    .insert(
+                        entity,
+                        ParticleEmitters {
+                            emitters: vec![emitter],
+                        },
+                    )
+                    .map_err(|e| Status::new("start_emitter()", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stop(emitter_ref_action: EmitterRefAction, world: &mut World) {
+        let mut entity_id = None;
+        INDEX.with(|index| {
+            if let Some(index) = &*index.borrow() {
+                entity_id = index.find_entity(&emitter_ref_action.scene_node_id);
+            }
+        });
+
+        if let Some(id) = entity_id {
+            let entity = world.entities().entity(id);
+
+            let mut emitters = world.write_storage::<ParticleEmitters>();
+            if let Some(emitters) = emitters.get_mut(entity) {
+                emitters
+                    .emitters
+                    .iter_mut()
+                    .filter(|emitter| emitter.id == emitter_ref_action.emitter_id)
+                    .for_each(|emitter| emitter.stop());
+            }
+        }
+    }
+}
+
+// 64-bit FNV-1a hash. Unlike the std hashers its output is fixed, so it is
+// stable across runs and Rust versions.
+fn fnv_hash(parts: &[&str]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for part in parts {
+        // Separates the parts, so that ("ab", "c") and ("a", "bc") differ.
+        for byte in part.bytes().chain([0xff]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv_hash_is_fixed() {
+        assert_eq!(fnv_hash(&["node", "smoke"]), 0xb4e3_2649_0b89_76b0);
+        assert_ne!(fnv_hash(&["ab", "c"]), fnv_hash(&["a", "bc"]));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::crust::{action, event, Action, EmitAction, Event};
+use log::error;
 use std::{cell::RefCell, sync::mpsc::Sender};
 
 // A global Action queue that receives/dispatches actions during the frame.
@@ -15,7 +16,7 @@ impl ActionQueue {
 
     pub fn push(&self, action: Action) {
         if let Err(e) = self.tx.send(action) {
-            eprintln!("🦀 Action channel closed: {}", e);
+            error!("Action channel closed: {}", e);
         }
     }
 
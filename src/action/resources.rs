@@ -0,0 +1,34 @@
+use crate::{
+    crust::{event, Event, PreloadAction, ResourceStatsAction, ResourceStatsEvent},
+    event::EventManager,
+    resources::{PendingPreloads, SpriteManager, TextureStats},
+    scene::SceneManager,
+};
+use specs::prelude::*;
+
+pub struct Resources;
+
+impl Resources {
+    pub fn preload(preload_action: PreloadAction, world: &mut World) {
+        world
+            .write_resource::<PendingPreloads>()
+            .0
+            .push(preload_action);
+    }
+
+    pub fn stats(
+        stats_action: ResourceStatsAction,
+        scene_manager: &SceneManager,
+        event_manager: &mut EventManager,
+        world: &World,
+    ) {
+        event_manager.handle(Event {
+            event_id: stats_action.event_id,
+            event: Some(event::Event::ResourceStats(ResourceStatsEvent {
+                textures: Some(world.read_resource::<TextureStats>().0.clone()),
+                sprites: Some(world.read_resource::<SpriteManager>().stats()),
+                tile_maps: Some(scene_manager.stats()),
+            })),
+        });
+    }
+}
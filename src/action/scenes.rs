@@ -1,48 +1,108 @@
 use crate::{
-    crust::SceneAction,
-    resources::{Viewport, WindowSize, WorldSize},
+    core::Status,
+    crust::{
+        event, tile_position_action, Box, Event, LayerOpacityAction, LayerVisibilityAction,
+        SceneAction, TileCoordinates, TilePositionAction, TilePositionEvent,
+    },
+    event::EventManager,
+    resources::{PendingTextureUnloads, Viewport, WindowSize, WorldSize},
     scene::SceneManager,
 };
-use sdl2::rect::Rect;
+use sdl2::rect::{Point, Rect};
 use specs::{World, WorldExt};
 
 pub struct Scenes;
 
 impl Scenes {
-    pub fn load(scene_action: SceneAction, scene_manager: &mut SceneManager, world: &mut World) {
-        if let Err(e) = scene_manager.load(&scene_action.resource, world) {
-            eprintln!("🦀 Failed to load scene: {:?}\nError: {e}", &scene_action);
-            return;
-        }
+    pub fn load(
+        scene_action: &SceneAction,
+        scene_manager: &mut SceneManager,
+        world: &mut World,
+    ) -> Result<(), Status> {
+        scene_manager.load(&scene_action.resource, world)?;
 
         let window_size = world.read_resource::<WindowSize>().0;
         let scene_bounds = scene_manager.scene_bounds();
 
         *world.write_resource() = WorldSize(scene_bounds);
-        *world.write_resource() = match &scene_action.viewport {
-            Some(viewport) => {
-                if viewport.left < 0
-                    || viewport.top < 0
-                    || viewport.left as u32 + viewport.width > scene_bounds.width()
-                    || viewport.top as u32 + viewport.height > scene_bounds.height()
-                    || viewport.width > window_size.width()
-                    || viewport.height > window_size.height()
-                {
-                    *world.write_resource() = Viewport(window_size);
-                    eprintln!(
-                        "viewport {:?} should be fully included in the world bounds: {:?} and cannot be larger than the window size: {:?}",
-                        &viewport, &scene_bounds, &window_size,
-                    );
-                }
-
-                Viewport(Rect::new(
-                    viewport.left,
-                    viewport.top,
-                    viewport.width,
-                    viewport.height,
-                ))
+        *world.write_resource() = Viewport(window_size);
+
+        if let Some(viewport) = &scene_action.viewport {
+            if viewport.left < scene_bounds.left()
+                || viewport.top < scene_bounds.top()
+                || viewport.left + viewport.width as i32 > scene_bounds.right()
+                || viewport.top + viewport.height as i32 > scene_bounds.bottom()
+                || viewport.width > window_size.width()
+                || viewport.height > window_size.height()
+            {
+                return Err(Status::invalid_argument(format!(
+                    "viewport {:?} should be fully included in the world bounds: {:?} and cannot be larger than the window size: {:?}",
+                    &viewport, &scene_bounds, &window_size,
+                )));
             }
-            None => Viewport(window_size),
+
+            *world.write_resource() = Viewport(Rect::new(
+                viewport.left,
+                viewport.top,
+                viewport.width,
+                viewport.height,
+            ));
         }
+        Ok(())
+    }
+
+    pub fn unload(scene_manager: &mut SceneManager, world: &mut World) -> Result<(), Status> {
+        let textures = scene_manager.unload(world)?;
+        world
+            .write_resource::<PendingTextureUnloads>()
+            .0
+            .extend(textures);
+        Ok(())
+    }
+
+    pub fn tile_position(
+        position_action: &TilePositionAction,
+        scene_manager: &SceneManager,
+        event_manager: &mut EventManager,
+    ) -> Result<(), Status> {
+        let grid = scene_manager
+            .grid()
+            .ok_or_else(|| Status::not_found("No scene is loaded"))?;
+        let (column, row) = match &position_action.position {
+            Some(tile_position_action::Position::WorldPosition(position)) => grid.tile_at(
+                Point::new(position.x.floor() as i32, position.y.floor() as i32),
+            ),
+            Some(tile_position_action::Position::Tile(tile)) => (tile.column, tile.row),
+            None => return Err(Status::invalid_argument("Missing position")),
+        };
+
+        let bounds = grid.tile_rect(column, row);
+        event_manager.handle(Event {
+            event_id: position_action.event_id.clone(),
+            event: Some(event::Event::TilePosition(TilePositionEvent {
+                tile: Some(TileCoordinates { column, row }),
+                bounds: Some(Box {
+                    left: bounds.left(),
+                    top: bounds.top(),
+                    width: bounds.width(),
+                    height: bounds.height(),
+                }),
+            })),
+        });
+        Ok(())
+    }
+
+    pub fn set_layer_visibility(
+        action: &LayerVisibilityAction,
+        scene_manager: &mut SceneManager,
+    ) -> Result<(), Status> {
+        scene_manager.set_layer_visibility(&action.layer_id, action.visible)
+    }
+
+    pub fn set_layer_opacity(
+        action: &LayerOpacityAction,
+        scene_manager: &mut SceneManager,
+    ) -> Result<(), Status> {
+        scene_manager.set_layer_opacity(&action.layer_id, action.opacity)
     }
 }
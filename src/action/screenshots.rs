@@ -0,0 +1,18 @@
+use crate::{core::Status, crust::ScreenshotAction, resources::PendingScreenshots};
+use specs::prelude::*;
+
+pub struct Screenshots;
+
+impl Screenshots {
+    pub fn take(screenshot_action: &ScreenshotAction, world: &mut World) -> Result<(), Status> {
+        if screenshot_action.filename.is_empty() {
+            return Err(Status::invalid_argument("screenshot(): missing filename"));
+        }
+
+        world
+            .write_resource::<PendingScreenshots>()
+            .0
+            .push(screenshot_action.filename.clone());
+        Ok(())
+    }
+}
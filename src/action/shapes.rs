@@ -0,0 +1,50 @@
+use super::INDEX;
+use crate::{
+    components::{Position, ShapeInfo},
+    core::Status,
+    crust::ShapeAction,
+};
+use specs::prelude::*;
+
+pub struct Shapes;
+
+impl Shapes {
+    pub fn set(shape_action: &ShapeAction, world: &mut World) -> Result<(), Status> {
+        let shape = match &shape_action.shape {
+            Some(shape) => shape,
+            None => return Ok(()),
+        };
+
+        let mut entity_id = None;
+        INDEX.with(|index| {
+            if let Some(index) = &*index.borrow() {
+                entity_id = index.find_entity(&shape_action.scene_node_id);
+            }
+        });
+
+        let entity_id = entity_id.ok_or_else(|| {
+            Status::not_found(format!(
+                "set_shape(): scene node '{}' not found",
+                &shape_action.scene_node_id
+            ))
+        })?;
+
+        let entity = world.entities().entity(entity_id);
+        let mut shapes = world.write_storage::<ShapeInfo>();
+        let mut positions = world.write_storage::<Position>();
+        match (shapes.get_mut(entity), positions.get_mut(entity)) {
+            (Some(shape_info), Some(position)) => {
+                shape_info.shape = shape.clone();
+                let bbox = shape_info.bounding_box();
+                position.0.resize(bbox.width(), bbox.height());
+            }
+            _ => {
+                return Err(Status::invalid_argument(format!(
+                    "set_shape(): scene node '{}' is not a shape",
+                    &shape_action.scene_node_id
+                )))
+            }
+        }
+        Ok(())
+    }
+}
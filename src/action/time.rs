@@ -0,0 +1,86 @@
+use super::INDEX;
+use crate::{
+    components::NodeTimeScale,
+    core::Status,
+    crust::{PauseAction, TimeScaleAction},
+    resources::TimeScale,
+};
+use specs::prelude::*;
+
+pub struct Time;
+
+// Scaled steps are computed with Duration::mul_f64, which panics on overflow.
+// The range also rejects NaN and infinite scales.
+const MAX_TIME_SCALE: f64 = 100.0;
+
+impl Time {
+    pub fn pause(_pause_action: PauseAction, world: &mut World) {
+        world.write_resource::<TimeScale>().paused = true;
+    }
+
+    pub fn resume(_pause_action: PauseAction, world: &mut World) {
+        world.write_resource::<TimeScale>().paused = false;
+    }
+
+    pub fn set_scale(time_scale_action: &TimeScaleAction, world: &mut World) -> Result<(), Status> {
+        if !(0.0..=MAX_TIME_SCALE).contains(&time_scale_action.scale) {
+            return Err(Status::invalid_argument(format!(
+                "set_time_scale(): Time scale must be in [0, {}] but was {}",
+                MAX_TIME_SCALE, time_scale_action.scale
+            )));
+        }
+
+        if time_scale_action.scene_node_id.is_empty() {
+            world.write_resource::<TimeScale>().scale = time_scale_action.scale;
+            return Ok(());
+        }
+
+        let mut entity_id = None;
+        INDEX.with(|index| {
+            if let Some(index) = &*index.borrow() {
+                entity_id = index.find_entity(&time_scale_action.scene_node_id);
+            }
+        });
+
+        let entity_id = entity_id.ok_or_else(|| {
+            Status::not_found(format!(
+                "set_time_scale(): scene node '{}' not found",
+                &time_scale_action.scene_node_id
+            ))
+        })?;
+
+        let entity = world.entities().entity(entity_id);
+        let mut time_scales = world.write_storage::<NodeTimeScale>();
+        time_scales
+            .insert(entity, NodeTimeScale(time_scale_action.scale))
+            .map_err(|e| Status::new("set_time_scale()", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_scales_are_rejected() {
+        let mut world = World::new();
+        world.insert(TimeScale::default());
+
+        for scale in [-1.0, f64::NAN, f64::INFINITY, 1e300] {
+            let action = TimeScaleAction {
+                scale,
+                ..Default::default()
+            };
+            assert!(Time::set_scale(&action, &mut world).is_err());
+        }
+        assert_eq!(world.read_resource::<TimeScale>().scale, 1.0);
+
+        let action = TimeScaleAction {
+            scale: 0.5,
+            ..Default::default()
+        };
+        assert!(Time::set_scale(&action, &mut world).is_ok());
+        assert_eq!(world.read_resource::<TimeScale>().scale, 0.5);
+    }
+}
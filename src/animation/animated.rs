@@ -1,7 +1,7 @@
 use crate::{
     action::ActionQueue,
-    components::{Id, Position, Rotation, Scaling, SpriteInfo, Velocity},
-    crust::{HorizontalAlign, VerticalAlign},
+    components::{Id, Position, Rotation, Scaling, ShapeInfo, SpriteInfo, Velocity},
+    crust::{HorizontalAlign, Shape, VerticalAlign},
     resources::Sprite,
 };
 use sdl2::rect::Point;
@@ -12,8 +12,10 @@ pub struct Animated<'a> {
     pub velocity: &'a mut Velocity,
     pub rotation: &'a mut Rotation,
     pub scaling: &'a mut Scaling,
-    pub sprite_info: &'a mut SpriteInfo,
-    sprite: &'a Sprite,
+    // Scene nodes are either sprites or shapes.
+    pub sprite_info: Option<&'a mut SpriteInfo>,
+    sprite: Option<&'a Sprite>,
+    pub shape_info: Option<&'a mut ShapeInfo>,
     pub queue: Option<&'a ActionQueue>,
 }
 
@@ -24,8 +26,9 @@ impl<'a> Animated<'a> {
         velocity: &'a mut Velocity,
         rotation: &'a mut Rotation,
         scaling: &'a mut Scaling,
-        sprite_info: &'a mut SpriteInfo,
-        sprite: &'a Sprite,
+        sprite_info: Option<&'a mut SpriteInfo>,
+        sprite: Option<&'a Sprite>,
+        shape_info: Option<&'a mut ShapeInfo>,
         queue: Option<&'a ActionQueue>,
     ) -> Self {
         Animated {
@@ -36,19 +39,34 @@ impl<'a> Animated<'a> {
             scaling,
             sprite_info,
             sprite,
+            shape_info,
             queue,
         }
     }
 
+    /// Returns the current frame of a sprite node. Shape nodes are always on
+    /// frame 0.
+    pub fn frame_index(&self) -> usize {
+        self.sprite_info
+            .as_ref()
+            .map_or(0, |sprite_info| sprite_info.frame_index)
+    }
+
     /// Handles sprite frame changes taking care of sprite film alignments.
+    /// It has no effect on shape nodes.
     pub fn change_frame(
         &mut self,
         frame_index: usize,
         v_align: VerticalAlign,
         h_align: HorizontalAlign,
     ) {
+        let (sprite_info, sprite) = match (&mut self.sprite_info, self.sprite) {
+            (Some(sprite_info), Some(sprite)) => (sprite_info, sprite),
+            _ => return,
+        };
+
         let prev_aabb = self.position.0;
-        let mut next_aabb = self.sprite.frames[frame_index].bounding_box;
+        let mut next_aabb = sprite.frames[frame_index].bounding_box;
         next_aabb.reposition(self.position.0.top_left());
         next_aabb.resize(
             (next_aabb.width() as f64 * self.scaling.0 .0) as u32,
@@ -56,8 +74,8 @@ impl<'a> Animated<'a> {
         );
 
         self.position.0 = next_aabb;
-        self.sprite_info.frame_index = frame_index;
-        self.sprite_info.bounding_box = self.sprite.frames[frame_index].bounding_box;
+        sprite_info.frame_index = frame_index;
+        sprite_info.bounding_box = sprite.frames[frame_index].bounding_box;
 
         self.velocity.0 += Point::new(
             match h_align {
@@ -76,4 +94,17 @@ impl<'a> Animated<'a> {
             },
         );
     }
+
+    /// Replaces the shape of a shape node resizing its bounding box to fit.
+    pub fn change_shape(&mut self, shape: Shape) {
+        if let Some(shape_info) = &mut self.shape_info {
+            shape_info.shape = shape;
+
+            let bbox = shape_info.bounding_box();
+            self.position.0.resize(
+                (bbox.width() as f64 * self.scaling.0 .0) as u32,
+                (bbox.height() as f64 * self.scaling.0 .1) as u32,
+            );
+        }
+    }
 }
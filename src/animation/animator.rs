@@ -1,6 +1,7 @@
 use super::{
-    Animated, FrameListPerformer, FrameRangePerformer, Progressor, ProgressorImpl,
-    RotationPerformer, ScalingPerformer, TimerPerformer, TranslationPerformer,
+    Animated, EmitterPerformer, FrameListPerformer, FrameRangePerformer, Progressor,
+    ProgressorImpl, RotationPerformer, ScalingPerformer, ShapePerformer, TimerPerformer,
+    TranslationPerformer,
 };
 use crate::crust::Animation;
 use std::time::Duration;
@@ -66,6 +67,19 @@ impl Animator {
             )));
         }
 
+        if let Some(emitter) = animation.emitter {
+            self.progressors.push(Box::new(ProgressorImpl::new(
+                EmitterPerformer::new(emitter),
+                Duration::ZERO,
+            )));
+        }
+        if let Some(shape) = animation.shape {
+            self.progressors.push(Box::new(ProgressorImpl::new(
+                ShapePerformer::new(shape),
+                Duration::ZERO,
+            )));
+        }
+
         for progressor in &mut self.progressors {
             progressor.start(animated, speed);
         }
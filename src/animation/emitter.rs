@@ -0,0 +1,36 @@
+use super::{Animated, Performer};
+use crate::{
+    components::AnimationRunningState,
+    crust::{action, Action, EmitterAction, EmitterAnimation},
+};
+
+#[derive(Default)]
+pub struct EmitterPerformer {
+    emitter: EmitterAnimation,
+}
+
+impl Performer for EmitterPerformer {
+    fn start(&mut self, _animated: &mut Animated, _speed: f64) {}
+    fn stop(&mut self, _animated: &mut Animated) {}
+    fn pause(&mut self, _animated: &mut Animated) {}
+    fn resume(&mut self, _animated: &mut Animated) {}
+
+    fn execute(&mut self, animated: &mut Animated) -> AnimationRunningState {
+        if let Some(queue) = animated.queue {
+            queue.push(Action {
+                action: Some(action::Action::StartEmitter(EmitterAction {
+                    scene_node_id: animated.id.0.clone(),
+                    emitter_id: self.emitter.emitter_id.clone(),
+                    emitter: self.emitter.emitter.clone(),
+                })),
+            });
+        }
+        AnimationRunningState::Finished
+    }
+}
+
+impl EmitterPerformer {
+    pub fn new(emitter: EmitterAnimation) -> Self {
+        EmitterPerformer { emitter }
+    }
+}
@@ -33,6 +33,10 @@ impl Performer for FrameListPerformer {
     fn resume(&mut self, _animated: &mut Animated) {}
 
     fn execute(&mut self, animated: &mut Animated) -> AnimationRunningState {
+        if animated.sprite_info.is_none() {
+            return AnimationRunningState::Finished;
+        }
+
         self.index += 1;
         if self.index == self.frame_list.frame.len() {
             self.index = 0;
@@ -38,7 +38,11 @@ impl Performer for FrameRangePerformer {
     fn resume(&mut self, _animated: &mut Animated) {}
 
     fn execute(&mut self, animated: &mut Animated) -> AnimationRunningState {
-        let mut next_frame = animated.sprite_info.frame_index as i32 + self.step;
+        if animated.sprite_info.is_none() {
+            return AnimationRunningState::Finished;
+        }
+
+        let mut next_frame = animated.frame_index() as i32 + self.step;
         if next_frame == self.frame_range.end_frame {
             next_frame = self.frame_range.start_frame;
         }
@@ -49,7 +53,7 @@ impl Performer for FrameRangePerformer {
             HorizontalAlign::from_i32(self.frame_range.horizontal_align).unwrap(),
         );
 
-        if animated.sprite_info.frame_index as i32 == self.frame_range.end_frame - self.step
+        if animated.frame_index() as i32 == self.frame_range.end_frame - self.step
             && self.frame_range.repeat > 0
         {
             self.iteration += 1;
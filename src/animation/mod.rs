@@ -1,5 +1,6 @@
 mod animated;
 mod animator;
+mod emitter;
 mod frame_list;
 mod frame_range;
 mod performer;
@@ -7,6 +8,7 @@ mod progressor;
 mod rotation;
 mod scaling;
 mod script_runner;
+mod shape;
 mod testing;
 mod timer;
 mod translation;
@@ -15,11 +17,13 @@ pub use animated::Animated;
 pub use script_runner::ScriptRunner;
 
 use animator::Animator;
+use emitter::EmitterPerformer;
 use frame_list::FrameListPerformer;
 use frame_range::FrameRangePerformer;
 use performer::Performer;
 use progressor::{Progressor, ProgressorImpl};
 use rotation::RotationPerformer;
 use scaling::ScalingPerformer;
+use shape::ShapePerformer;
 use timer::TimerPerformer;
 use translation::TranslationPerformer;
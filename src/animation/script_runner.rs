@@ -63,6 +63,11 @@ impl ScriptRunner {
         self.state
     }
 
+    /// Index of the animation in the script that is currently running.
+    pub fn current_step(&self) -> i32 {
+        self.index
+    }
+
     fn reversed(&self) -> bool {
         self.speed < 0.0
     }
@@ -128,7 +133,7 @@ impl ScriptRunner {
                         y: animated.position.0.y() as f64,
                         z: 0.0,
                     }),
-                    frame_index: animated.sprite_info.frame_index as u32,
+                    frame_index: animated.frame_index() as u32,
                 }),
             );
         }
@@ -145,7 +150,7 @@ impl ScriptRunner {
                         y: animated.position.0.y() as f64,
                         z: 0.0,
                     }),
-                    frame_index: animated.sprite_info.frame_index as u32,
+                    frame_index: animated.frame_index() as u32,
                 }),
             );
         }
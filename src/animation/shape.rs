@@ -0,0 +1,27 @@
+use super::{Animated, Performer};
+use crate::{components::AnimationRunningState, crust::ShapeAnimation};
+
+#[derive(Default)]
+pub struct ShapePerformer {
+    shape: ShapeAnimation,
+}
+
+impl Performer for ShapePerformer {
+    fn start(&mut self, _animated: &mut Animated, _speed: f64) {}
+    fn stop(&mut self, _animated: &mut Animated) {}
+    fn pause(&mut self, _animated: &mut Animated) {}
+    fn resume(&mut self, _animated: &mut Animated) {}
+
+    fn execute(&mut self, animated: &mut Animated) -> AnimationRunningState {
+        if let Some(shape) = &self.shape.shape {
+            animated.change_shape(shape.clone());
+        }
+        AnimationRunningState::Finished
+    }
+}
+
+impl ShapePerformer {
+    pub fn new(shape: ShapeAnimation) -> Self {
+        ShapePerformer { shape }
+    }
+}
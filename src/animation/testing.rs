@@ -3,7 +3,7 @@ pub mod util {
     use crate::{
         animation::Animated,
         components::{Id, Position, Rotation, Scaling, SpriteInfo, Velocity},
-        resources::{Frame, Sprite},
+        resources::{Frame, Sprite, TileFlip},
     };
     use sdl2::rect::Rect;
 
@@ -26,9 +26,12 @@ pub mod util {
                 rotation: Rotation::default(),
                 scaling: Scaling::default(),
                 sprite_info: SpriteInfo {
+                    sprite_id: "foo".to_owned(),
                     texture_id: "foo".to_owned(),
                     frame_index: 0,
                     bounding_box: Rect::new(0, 0, 32, 32),
+                    flip: TileFlip::default(),
+                    sprite_ref: None,
                 },
                 sprite: Sprite {
                     texture_id: "foo".to_owned(),
@@ -58,6 +61,7 @@ pub mod util {
                             bitmask: None,
                         },
                     ],
+                    tiles: vec![],
                 },
             }
         }
@@ -69,8 +73,9 @@ pub mod util {
                 &mut self.velocity,
                 &mut self.rotation,
                 &mut self.scaling,
-                &mut self.sprite_info,
-                &self.sprite,
+                Some(&mut self.sprite_info),
+                Some(&self.sprite),
+                None,
                 None,
             )
         }
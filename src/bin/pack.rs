@@ -0,0 +1,70 @@
+//! Packs an assets directory into a single archive that the engine reads when
+//! `CrustConfig.asset_pack` is set.
+//!
+//! Usage: crust-pack <assets dir> <output file> [--no-compress]
+
+use crust_lib::{core::Status, resources::PackWriter};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let compress = !args.iter().any(|arg| arg == "--no-compress");
+    let paths: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    if paths.len() != 2 {
+        eprintln!("Usage: crust-pack <assets dir> <output file> [--no-compress]");
+        std::process::exit(2);
+    }
+
+    if let Err(e) = pack(Path::new(paths[0]), Path::new(paths[1]), compress) {
+        eprintln!("crust-pack: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn pack(root: &Path, output: &Path, compress: bool) -> Result<(), Status> {
+    let mut files = vec![];
+    list_files(root, &mut files)?;
+    files.sort();
+
+    let mut writer = PackWriter::new();
+    for file in &files {
+        let name = asset_name(root, file);
+        // Images are compressed already.
+        let compress = compress && file.extension().map_or(true, |ext| ext != "png");
+        writer.add(&name, std::fs::read(file)?, compress)?;
+        println!("{name}");
+    }
+
+    // Flushing explicitly reports errors that dropping the writer would hide.
+    let mut file = BufWriter::new(File::create(output)?);
+    writer.write(&mut file)?;
+    file.flush()?;
+    println!("Packed {} assets into '{}'", files.len(), output.display());
+    Ok(())
+}
+
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Status> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        match path.is_dir() {
+            true => list_files(&path, files)?,
+            false => files.push(path),
+        }
+    }
+    Ok(())
+}
+
+// Assets are addressed by their path relative to the assets directory, always
+// separated by '/'.
+fn asset_name(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,153 @@
+//! Checks every sprite sheet, tile map and Tiled tileset under an assets directory and prints
+//! a report of the problems found. Exits with an error status if any asset is
+//! invalid.
+//!
+//! Usage: crust-validate <assets dir>
+
+use crust_lib::{
+    core::Status,
+    resources::{
+        read_tmx, read_tsx, validate_sprite, validate_tile_map, AssetSource, DirectorySource,
+        ResourceLoader, Sprite, SpriteLoader, TileMap, TileMapLoader,
+    },
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 1 {
+        eprintln!("Usage: crust-validate <assets dir>");
+        std::process::exit(2);
+    }
+
+    match validate(Path::new(&args[0])) {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("crust-validate: {e}");
+            std::process::exit(2);
+        }
+    }
+}
+
+enum Asset {
+    Sprite(Sprite),
+    TileMap(TileMap),
+}
+
+// Prints the report and returns whether all assets are valid.
+fn validate(root: &Path) -> Result<bool, Status> {
+    let source = DirectorySource::new(&root.to_string_lossy());
+
+    let mut files = vec![];
+    list_files(root, &mut files)?;
+    files.sort();
+
+    // Parse everything first, so that tile maps can be checked against their
+    // tilesets.
+    let mut assets = vec![];
+    for file in &files {
+        let extension = match file.extension().and_then(|ext| ext.to_str()) {
+            Some(extension @ ("json" | "tmx" | "tsx")) => extension,
+            _ => continue,
+        };
+        let resource = resource_name(root, file);
+        let asset = load_asset(&source, &resource, extension);
+        assets.push((resource, extension, asset));
+    }
+
+    let tileset_frames: HashMap<&str, usize> = assets
+        .iter()
+        .filter_map(|(resource, _, asset)| match asset {
+            Ok(Some(Asset::Sprite(sprite))) => Some((resource.as_str(), sprite.frames.len())),
+            _ => None,
+        })
+        .collect();
+
+    let (mut checked, mut invalid) = (0, 0);
+    for (resource, extension, asset) in &assets {
+        let problems = match asset {
+            Ok(Some(Asset::Sprite(sprite))) => {
+                let mut problems = validate_sprite(sprite);
+                if !source.contains(&format!("{}.png", sprite.texture_id)) {
+                    problems.push(format!("texture '{}.png' not found", sprite.texture_id));
+                }
+                problems
+            }
+            Ok(Some(Asset::TileMap(map))) => {
+                validate_tile_map(map, |tileset| tileset_frames.get(tileset).copied())
+            }
+            // Not an asset, e.g. a config file.
+            Ok(None) => continue,
+            Err(e) => vec![e.message().to_owned()],
+        };
+
+        checked += 1;
+        match problems.is_empty() {
+            true => println!("ok      {resource}.{extension}"),
+            false => {
+                invalid += 1;
+                println!("invalid {resource}.{extension}");
+                for problem in problems {
+                    println!("        - {problem}");
+                }
+            }
+        }
+    }
+
+    println!("\n{checked} assets checked, {invalid} invalid");
+    Ok(invalid == 0)
+}
+
+// Tells json sprite sheets and tile maps apart by their required fields.
+fn load_asset(
+    source: &DirectorySource,
+    resource: &str,
+    extension: &str,
+) -> Result<Option<Asset>, Status> {
+    match extension {
+        "tmx" => return Ok(Some(Asset::TileMap(read_tmx(source, resource)?))),
+        "tsx" => return Ok(Some(Asset::Sprite(read_tsx(source, resource)?))),
+        _ => {}
+    }
+
+    let json = source.read(&format!("{resource}.json"))?;
+    let value: serde_json::Value = serde_json::from_slice(&json)
+        .map_err(|e| Status::new(&format!("Failed to parse '{resource}.json'"), e))?;
+
+    if value.get("layers").is_some() {
+        Ok(Some(Asset::TileMap(
+            TileMapLoader {}.load(source, resource)?,
+        )))
+    } else if value.get("frames").is_some() {
+        Ok(Some(Asset::Sprite(SpriteLoader {}.load(source, resource)?)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Status> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        match path.is_dir() {
+            true => list_files(&path, files)?,
+            false => files.push(path),
+        }
+    }
+    Ok(())
+}
+
+// Resources are addressed by their path relative to the assets directory
+// without extension, always separated by '/'.
+fn resource_name(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -1,9 +1,17 @@
 mod animation;
 mod collision;
+mod particles;
+mod properties;
+mod screen_space;
 mod scrollilng;
+mod shapes;
 mod sprites;
 
 pub use animation::{Animation, AnimationRunningState};
 pub use collision::{Collisions, RigidBody};
+pub use particles::ParticleEmitters;
+pub use properties::Properties;
+pub use screen_space::ScreenSpace;
 pub use scrollilng::ScrollingInfo;
+pub use shapes::ShapeInfo;
 pub use sprites::*;
@@ -0,0 +1,10 @@
+use crate::particles::Emitter;
+use specs::prelude::*;
+use specs_derive::Component;
+
+/// Particle emitters attached to a scene node.
+#[derive(Component, Default)]
+#[storage(VecStorage)]
+pub struct ParticleEmitters {
+    pub emitters: Vec<Emitter>,
+}
@@ -0,0 +1,10 @@
+use crate::crust::PropertyValue;
+use specs::prelude::*;
+use specs_derive::Component;
+use std::collections::HashMap;
+
+/// Custom properties of a scene node created from a Tiled map object that are
+/// not mapped to other components. The host can query them.
+#[derive(Component, Default, Debug)]
+#[storage(VecStorage)]
+pub struct Properties(pub HashMap<String, PropertyValue>);
@@ -0,0 +1,90 @@
+use crate::crust::Anchor;
+use sdl2::rect::Rect;
+use specs::prelude::*;
+use specs_derive::Component;
+
+/// Marks a scene node that is rendered fixed to the window instead of the
+/// world. Its Position is an offset from the anchor point of the window.
+#[derive(Component, Debug)]
+#[storage(VecStorage)]
+pub struct ScreenSpace {
+    pub anchor: Anchor,
+}
+
+impl ScreenSpace {
+    /// Returns the on-screen rectangle of a node with `position` in a window
+    /// of `window_size`.
+    pub fn screen_position(&self, position: Rect, window_size: Rect) -> Rect {
+        let (x_factor, y_factor) = match self.anchor {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Centre => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        };
+
+        Rect::new(
+            position.x() + x_factor * (window_size.width() as i32 - position.width() as i32) / 2,
+            position.y() + y_factor * (window_size.height() as i32 - position.height() as i32) / 2,
+            position.width(),
+            position.height(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchor_top_left() {
+        let hud = ScreenSpace {
+            anchor: Anchor::TopLeft,
+        };
+        assert_eq!(
+            hud.screen_position(Rect::new(10, 5, 32, 16), Rect::new(0, 0, 320, 240)),
+            Rect::new(10, 5, 32, 16)
+        );
+    }
+
+    #[test]
+    fn anchor_bottom_right() {
+        let hud = ScreenSpace {
+            anchor: Anchor::BottomRight,
+        };
+        assert_eq!(
+            hud.screen_position(Rect::new(-10, -5, 32, 16), Rect::new(0, 0, 320, 240)),
+            Rect::new(278, 219, 32, 16)
+        );
+    }
+
+    #[test]
+    fn anchor_centre() {
+        let hud = ScreenSpace {
+            anchor: Anchor::Centre,
+        };
+        assert_eq!(
+            hud.screen_position(Rect::new(0, 0, 32, 16), Rect::new(0, 0, 320, 240)),
+            Rect::new(144, 112, 32, 16)
+        );
+    }
+
+    #[test]
+    fn anchor_follows_window_size() {
+        let hud = ScreenSpace {
+            anchor: Anchor::Right,
+        };
+        assert_eq!(
+            hud.screen_position(Rect::new(0, 0, 32, 16), Rect::new(0, 0, 320, 240)),
+            Rect::new(288, 112, 32, 16)
+        );
+        assert_eq!(
+            hud.screen_position(Rect::new(0, 0, 32, 16), Rect::new(0, 0, 640, 480)),
+            Rect::new(608, 232, 32, 16)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::crust::{shape, Shape, Vector};
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+};
+use specs::prelude::*;
+use specs_derive::Component;
+
+/// A scene node that is drawn as a primitive shape instead of a sprite.
+#[derive(Component, Debug)]
+#[storage(VecStorage)]
+pub struct ShapeInfo {
+    pub shape: Shape,
+}
+
+impl ShapeInfo {
+    pub fn colour(&self) -> Color {
+        self.shape.colour.as_ref().map_or(Color::WHITE, Color::from)
+    }
+
+    /// Returns the bounding box of the shape with its top-left corner at the
+    /// origin.
+    pub fn bounding_box(&self) -> Rect {
+        let (width, height) = match &self.shape.shape {
+            Some(shape::Shape::Rectangle(rectangle)) => (rectangle.width, rectangle.height),
+            Some(shape::Shape::Circle(circle)) => (2 * circle.radius + 1, 2 * circle.radius + 1),
+            Some(shape::Shape::RoundedBox(rounded_box)) => (rounded_box.width, rounded_box.height),
+            Some(shape::Shape::Line(_)) | Some(shape::Shape::Polygon(_)) => {
+                let points = self.points();
+                (
+                    points.iter().map(|p| p.x()).max().unwrap_or(0) as u32 + 1,
+                    points.iter().map(|p| p.y()).max().unwrap_or(0) as u32 + 1,
+                )
+            }
+            None => (0, 0),
+        };
+        Rect::new(0, 0, width.max(1), height.max(1))
+    }
+
+    /// Returns the points of a line or polygon translated so that the
+    /// top-left corner of their bounding box is at the origin.
+    pub fn points(&self) -> Vec<Point> {
+        let points: Vec<&Vector> = match &self.shape.shape {
+            Some(shape::Shape::Line(line)) => line.from.iter().chain(line.to.iter()).collect(),
+            Some(shape::Shape::Polygon(polygon)) => polygon.point.iter().collect(),
+            _ => vec![],
+        };
+
+        let min_x = points.iter().map(|p| p.x as i32).min().unwrap_or(0);
+        let min_y = points.iter().map(|p| p.y as i32).min().unwrap_or(0);
+        points
+            .iter()
+            .map(|p| Point::new(p.x as i32 - min_x, p.y as i32 - min_y))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crust::{CircleShape, LineShape, PolygonShape, RectangleShape, RgBa};
+
+    fn shape_info(shape: shape::Shape) -> ShapeInfo {
+        ShapeInfo {
+            shape: Shape {
+                shape: Some(shape),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn vector(x: f64, y: f64) -> Vector {
+        Vector {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rectangle_bounding_box() {
+        let info = shape_info(shape::Shape::Rectangle(RectangleShape {
+            width: 16,
+            height: 8,
+        }));
+        assert_eq!(info.bounding_box(), Rect::new(0, 0, 16, 8));
+    }
+
+    #[test]
+    fn circle_bounding_box() {
+        let info = shape_info(shape::Shape::Circle(CircleShape { radius: 5 }));
+        assert_eq!(info.bounding_box(), Rect::new(0, 0, 11, 11));
+    }
+
+    #[test]
+    fn line_points_are_normalised() {
+        let info = shape_info(shape::Shape::Line(LineShape {
+            from: Some(vector(-4.0, 10.0)),
+            to: Some(vector(6.0, 2.0)),
+            thickness: 0,
+        }));
+        assert_eq!(info.points(), vec![Point::new(0, 8), Point::new(10, 0)]);
+        assert_eq!(info.bounding_box(), Rect::new(0, 0, 11, 9));
+    }
+
+    #[test]
+    fn polygon_bounding_box() {
+        let info = shape_info(shape::Shape::Polygon(PolygonShape {
+            point: vec![vector(0.0, 0.0), vector(20.0, 5.0), vector(5.0, 30.0)],
+        }));
+        assert_eq!(info.bounding_box(), Rect::new(0, 0, 21, 31));
+    }
+
+    #[test]
+    fn colour_defaults_to_white() {
+        let mut info = shape_info(shape::Shape::Circle(CircleShape { radius: 1 }));
+        assert_eq!(info.colour(), Color::WHITE);
+
+        info.shape.colour = Some(RgBa {
+            red: 300,
+            green: 128,
+            blue: 0,
+            alpha: 64,
+        });
+        assert_eq!(info.colour(), Color::RGBA(255, 128, 0, 64));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::resources::{ResourceRef, TileFlip};
 use sdl2::rect::{Point, Rect};
 use specs::prelude::*;
 use specs_derive::Component;
@@ -20,6 +21,17 @@ impl Default for Position {
     }
 }
 
+/// Top-left corner of a scene node before the last simulation step. It is
+/// used for interpolating the rendered position between steps.
+#[derive(Component, Debug)]
+#[storage(VecStorage)]
+pub struct PreviousPosition(pub Point);
+
+/// Drawing order of a scene node. Nodes with higher depth are drawn on top.
+#[derive(Component, Default, Debug)]
+#[storage(VecStorage)]
+pub struct Depth(pub i32);
+
 #[derive(Component, Debug)]
 #[storage(VecStorage)]
 pub struct Velocity(pub Point);
@@ -47,10 +59,25 @@ impl Default for Scaling {
     }
 }
 
+/// Scales the time that passes for the animations of a scene node, on top of
+/// the global TimeScale.
+#[derive(Component, Debug)]
+#[storage(VecStorage)]
+pub struct NodeTimeScale(pub f64);
+
 #[derive(Component, Debug)]
 #[storage(VecStorage)]
 pub struct SpriteInfo {
+    // Sprite sheet of the node in the SpriteManager and the texture its
+    // frames are drawn from, which differ for tiles of Tiled tilesets.
+    pub sprite_id: String,
     pub texture_id: String,
     pub frame_index: usize,
     pub bounding_box: Rect,
+
+    // Set for nodes created from flipped Tiled tile objects.
+    pub flip: TileFlip,
+
+    // Keeps the sprite sheet loaded while the node is alive.
+    pub sprite_ref: Option<ResourceRef>,
 }
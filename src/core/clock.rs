@@ -0,0 +1,83 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Source of time for the engine. Time is measured as the duration since an
+/// arbitrary fixed point, so it can only move forward.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// Monotonic clock that follows real time.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only moves when it is advanced, so that tests can control time
+/// precisely. Clones share the same time, also across threads.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    // Nanoseconds since the start of the clock.
+    now: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.now.load(Ordering::SeqCst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_clones_share_time() {
+        let clock = ManualClock::new();
+        let handle = clock.clone();
+        assert_eq!(clock.now(), Duration::ZERO);
+
+        handle.advance(Duration::from_millis(16));
+        handle.advance(Duration::from_millis(4));
+        assert_eq!(clock.now(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn manual_clock_advances_from_other_threads() {
+        let clock = ManualClock::new();
+        let handle = clock.clone();
+        std::thread::spawn(move || handle.advance(Duration::from_millis(16)))
+            .join()
+            .unwrap();
+        assert_eq!(clock.now(), Duration::from_millis(16));
+    }
+}
@@ -1,31 +1,48 @@
-use super::FpsCounter;
+use super::{
+    init_logging, next_log_frame, positions_checksum, refresh_sprite_nodes, AssetWatcher, Clock,
+    FixedTimestep, FpsCounter, FrameStats, Recorder, Replayer, Session, SystemClock,
+};
 use crate::{
     action::{ActionExecutor, ActionQueue, Index, ACTION_QUEUE, INDEX},
     components::{
-        Animation, Collisions, Id, Position, RigidBody, Rotation, Scaling, ScrollingInfo,
-        SpriteInfo, Velocity,
+        Animation, Collisions, Depth, Id, NodeTimeScale, ParticleEmitters, Position,
+        PreviousPosition, Properties, RigidBody, Rotation, Scaling, ScreenSpace, ScrollingInfo,
+        ShapeInfo, SpriteInfo, Velocity,
     },
     core::{EventPump, Status},
-    crust::{user_input, Action, CrustConfig, UserInput},
+    crust::{
+        event, user_input, AssetEvent, AssetType, CrustConfig, Event, FullscreenMode, PreloadEvent,
+        ResourceBudget, ScalingMode, UserInput, Window,
+    },
     event::EventManager,
     input::InputManager,
-    resources::{SpriteManager, TextureManager, Viewport, WindowSize, WorldSize},
-    scene::SceneManager,
-    systems::{render, AnimatorSystem, CollisionSystem, MovementSystem, ScrollingSystem},
+    resources::{
+        AssetSource, DirectorySource, Interpolation, LayeredSource, PackSource, PendingPreloads,
+        PendingScreenshots, PendingTextureUnloads, PendingWindowChanges, Preload, Preloaded,
+        SpriteManager, TextureManager, TextureStats, TimeScale, UnscaledTime, Viewport,
+        WindowChange, WindowSize, WorldSize,
+    },
+    scene::{SceneManager, TileAnimations},
+    systems::{
+        render, AnimatorSystem, CollisionSystem, InterpolationSystem, MovementSystem,
+        ParticleSystem, ScrollingSystem, TileAnimationSystem,
+    },
 };
+use log::{debug, error, info, warn};
 use sdl2::{
     image::{self, InitFlag},
     rect::Rect,
-    render::WindowCanvas,
+    render::{TextureCreator, WindowCanvas},
+    video::{FullscreenType, WindowContext},
 };
 use specs::prelude::*;
 use std::{
-    sync::mpsc::{self, Sender},
-    time::{Duration, SystemTime},
+    sync::{mpsc, Arc},
+    time::Duration,
 };
 
 pub struct Core {
-    resource_path: String,
+    asset_source: Arc<dyn AssetSource>,
 
     pub world: World,
     pub executor: ActionExecutor,
@@ -33,9 +50,18 @@ pub struct Core {
     pub event_manager: EventManager,
     pub scene_manager: SceneManager,
 
+    dispatcher: Dispatcher<'static, 'static>,
+    clock: Arc<dyn Clock>,
+    // Time of the start of the last frame, unset before the first frame.
+    prev_time: Option<Duration>,
     fps_counter: FpsCounter,
-
-    tx: Sender<Action>,
+    timestep: FixedTimestep,
+    // Minimum time between rendered frames if the render rate is capped.
+    frame_interval: Option<Duration>,
+    session: Session,
+    asset_watcher: Option<AssetWatcher>,
+    resource_budget: ResourceBudget,
+    preloads: Vec<Preload>,
 
     _sdl_context: sdl2::Sdl,
     _video_subsystem: sdl2::VideoSubsystem,
@@ -46,33 +72,77 @@ pub struct Core {
 
 impl Core {
     pub fn init(config: CrustConfig) -> Result<Self, Status> {
+        Self::init_with_clock(config, Arc::new(SystemClock::new()))
+    }
+
+    /// Initialises the engine with a custom source of time, e.g. a
+    /// ManualClock for tests that need to control the frame timing.
+    pub fn init_with_clock(config: CrustConfig, clock: Arc<dyn Clock>) -> Result<Self, Status> {
+        init_logging(&config.logging.clone().unwrap_or_default());
+        debug!("config: {:?}", config);
+
+        if config.headless {
+            // Needs to be set before the video subsystem is initialised. SDL
+            // before 2.0.22 only reads the driver from the environment, not
+            // from hints.
+            std::env::set_var("SDL_VIDEODRIVER", "dummy");
+        }
+
         let sdl_context = sdl2::init()?;
         let video_subsystem = sdl_context.video()?;
 
-        let event_pump = EventPump::new(&sdl_context)?;
-
         if config.window == None {
             return Err(Status::invalid_argument("Game window was not configured."));
         }
 
+        let asset_source = open_asset_source(&config)?;
         let window_config = &config.window.unwrap();
-        let window = video_subsystem
-            .window(
-                &window_config.title,
-                window_config.width as u32,
-                window_config.height as u32,
-            )
-            .position_centered()
+        let logical_size = logical_size(window_config);
+
+        let mut window_builder = video_subsystem.window(
+            &window_config.title,
+            window_config.width as u32,
+            window_config.height as u32,
+        );
+        window_builder.position_centered();
+        if config.headless {
+            window_builder.hidden();
+        }
+        if window_config.resizable {
+            window_builder.resizable();
+        }
+        match FullscreenMode::from_i32(window_config.fullscreen) {
+            Some(FullscreenMode::Fullscreen) => {
+                window_builder.fullscreen();
+            }
+            Some(FullscreenMode::Borderless) => {
+                window_builder.fullscreen_desktop();
+            }
+            _ => {}
+        }
+        let window = window_builder
             .build()
             .expect("could not initialize video subsystem");
-        let canvas = window
-            .into_canvas()
-            .build()
-            .expect("could not make a canvas");
+
+        let mut canvas_builder = window.into_canvas();
+        if config.headless {
+            canvas_builder = canvas_builder.software();
+        }
+        let mut canvas = canvas_builder.build().expect("could not make a canvas");
+
+        // SDL takes care of scaling the rendered frame to the window, adding
+        // letterbox bars where needed.
+        canvas
+            .set_logical_size(logical_size.width(), logical_size.height())
+            .map_err(|e| Status::new("Failed to set logical size", e))?;
+        canvas.set_integer_scale(window_config.scaling == ScalingMode::Integer as i32)?;
+
+        let event_pump = EventPump::new(&sdl_context, logical_size)?;
 
         let mut world = World::new();
         world.register::<Id>();
         world.register::<Position>();
+        world.register::<PreviousPosition>();
         world.register::<SpriteInfo>();
         world.register::<Velocity>();
         world.register::<Rotation>();
@@ -81,24 +151,36 @@ impl Core {
         world.register::<ScrollingInfo>();
         world.register::<Collisions>();
         world.register::<RigidBody>();
+        world.register::<ParticleEmitters>();
+        world.register::<Properties>();
+        world.register::<ScreenSpace>();
+        world.register::<ShapeInfo>();
+        world.register::<Depth>();
+        world.register::<NodeTimeScale>();
 
-        let sprite_manager = SpriteManager::create(&config.assets_path);
+        let resource_budget = config.resource_budget.unwrap_or_default();
+        let mut sprite_manager = SpriteManager::create(Arc::clone(&asset_source));
+        sprite_manager.set_budget(resource_budget.sprites);
         world.insert(sprite_manager);
-        world.insert(Duration::ZERO);
+        // Systems always see the fixed simulation step, scaled by the
+        // TimeScale, as the time since the last frame.
+        let timing = config.timing.unwrap_or_default();
+        let timestep = FixedTimestep::new(&timing)?;
+        world.insert(timestep.step());
+        world.insert(UnscaledTime(timestep.step()));
+        world.insert(TimeScale::default());
         world.insert(WorldSize(Rect::new(0, 0, 0, 0)));
-        world.insert(WindowSize(Rect::new(
-            0,
-            0,
-            window_config.width as u32,
-            window_config.height as u32,
-        )));
-        world.insert(Viewport(Rect::new(
-            0,
-            0,
-            window_config.width as u32,
-            window_config.height as u32,
-        )));
+        world.insert(WindowSize(logical_size));
+        world.insert(Viewport(logical_size));
         world.insert(config.debug.unwrap_or_default());
+        world.insert(PendingScreenshots::default());
+        world.insert(PendingTextureUnloads::default());
+        world.insert(PendingPreloads::default());
+        world.insert(TextureStats::default());
+        world.insert(FrameStats::default());
+        world.insert(PendingWindowChanges::default());
+        world.insert(TileAnimations::default());
+        world.insert(Interpolation::default());
 
         let (tx, rx) = mpsc::channel();
         ACTION_QUEUE.with(|queue| {
@@ -108,18 +190,71 @@ impl Core {
             *index.borrow_mut() = Some(Index::new());
         });
 
+        let session = match (config.record.is_empty(), config.replay.is_empty()) {
+            (true, true) => Session::Live,
+            (false, true) => Session::Recording(Recorder::create(&config.record)?),
+            (true, false) => Session::Replaying(Replayer::open(&config.replay)?),
+            (false, false) => {
+                return Err(Status::invalid_argument(
+                    "A session cannot be recorded and replayed at the same time.",
+                ))
+            }
+        };
+
+        // Override directories are watched too, as their assets shadow the
+        // base ones.
+        let asset_watcher = match config.hot_reload {
+            true => {
+                let mut roots = config.asset_overrides.clone();
+                roots.push(config.assets_path.clone());
+                Some(AssetWatcher::new(&roots))
+            }
+            false => None,
+        };
+
         let executor = ActionExecutor::new(rx, &mut world);
-        let scene_manager = SceneManager::new(&config.assets_path);
+        let mut scene_manager = SceneManager::new(Arc::clone(&asset_source));
+        scene_manager.set_budget(resource_budget.tile_maps);
+
+        let interpolation = InterpolationSystem::new();
+        let animations = AnimatorSystem::new(ActionQueue::new(tx.clone()));
+        let movement = MovementSystem::new();
+        let collisions = CollisionSystem::new(ActionQueue::new(tx));
+        let scrolling = ScrollingSystem::new();
+        let particles = ParticleSystem::new();
+        let tile_animations = TileAnimationSystem::new();
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(interpolation, "Interpolation", &[])
+            .with(animations, "Animation", &["Interpolation"])
+            .with(movement, "Movement", &["Animation"])
+            .with(collisions, "Collisions", &["Animation", "Movement"])
+            .with(scrolling, "Scrolling", &["Interpolation"])
+            .with(particles, "Particles", &["Movement"])
+            .with(tile_animations, "TileAnimations", &[])
+            .build();
+        dispatcher.setup(&mut world);
 
         Ok(Core {
-            resource_path: config.assets_path,
+            asset_source,
             world,
             executor,
             input_manager: InputManager::new(),
             event_manager: EventManager::new(),
             scene_manager,
-            fps_counter: FpsCounter::new(),
-            tx,
+            dispatcher,
+            fps_counter: FpsCounter::new(clock.clone()),
+            clock,
+            prev_time: None,
+            timestep,
+            frame_interval: match timing.render_rate {
+                0 => None,
+                rate => Some(Duration::from_secs(1) / rate),
+            },
+            session,
+            asset_watcher,
+            resource_budget,
+            preloads: vec![],
             _sdl_context: sdl_context,
             _video_subsystem: video_subsystem,
             _image_context: image::init(InitFlag::PNG | InitFlag::JPG)?,
@@ -130,78 +265,350 @@ impl Core {
 
     pub fn run(&mut self) {
         let texture_creator = self.canvas.texture_creator();
-        let mut texture_manager = TextureManager::new(&self.resource_path, &texture_creator);
+        let mut texture_manager =
+            TextureManager::new(Arc::clone(&self.asset_source), &texture_creator);
+        texture_manager.set_budget(self.resource_budget.textures);
 
-        let animations = AnimatorSystem::new(ActionQueue::new(self.tx.clone()));
-        let movement = MovementSystem::new();
-        let collisions = CollisionSystem::new(ActionQueue::new(self.tx.clone()));
-        let scrolling = ScrollingSystem::new();
+        while self.frame(&texture_creator, &mut texture_manager) {}
+    }
 
-        let mut dispatcher = DispatcherBuilder::new()
-            .with(animations, "Animation", &[])
-            .with(movement, "Movement", &["Animation"])
-            .with(collisions, "Collisions", &["Animation", "Movement"])
-            .with(scrolling, "Scrolling", &[])
-            .build();
-        dispatcher.setup(&mut self.world);
+    /// Runs up to `frames` frames of the game loop and returns false if the
+    /// game quit before they were over. Together with a ManualClock it steps
+    /// the engine deterministically, e.g. in tests. Textures are only cached
+    /// for the duration of a call.
+    pub fn run_frames(&mut self, frames: u32) -> bool {
+        let texture_creator = self.canvas.texture_creator();
+        let mut texture_manager =
+            TextureManager::new(Arc::clone(&self.asset_source), &texture_creator);
+        texture_manager.set_budget(self.resource_budget.textures);
 
-        let mut prev_time = SystemTime::now();
+        (0..frames).all(|_| self.frame(&texture_creator, &mut texture_manager))
+    }
 
-        'game: loop {
-            self.fps_counter.start_frame();
+    /// Runs a single frame of the game loop. Returns false if the game quit.
+    fn frame<'l>(
+        &mut self,
+        texture_creator: &'l TextureCreator<WindowContext>,
+        texture_manager: &mut TextureManager<'l, WindowContext>,
+    ) -> bool {
+        self.fps_counter.start_frame();
+        next_log_frame();
 
-            // Input event handling.
-            'events: loop {
-                match self.event_pump.poll().event {
-                    Some(user_input::Event::NoEvent(..)) => {
-                        break 'events;
-                    }
-                    Some(user_input::Event::QuitEvent(..)) => {
-                        break 'game;
-                    }
-                    Some(user_input::Event::KeyEvent(event)) if event.key == "Q" => break 'game,
-                    Some(user_input::Event::KeyEvent(event)) => {
-                        self.input_manager.handle(UserInput {
-                            event: Some(user_input::Event::KeyEvent(event)),
-                        });
-                    }
-                    // Some(event) => println!("{:#?}", event),
-                    _ => {}
+        if let Session::Replaying(replayer) = &mut self.session {
+            match replayer.next_frame() {
+                Ok(true) => {}
+                Ok(false) => {
+                    info!("Replay finished, continuing live");
+                    self.session = Session::Live;
+                }
+                Err(e) => {
+                    error!("Replay stopped: {e}");
+                    self.session = Session::Live;
                 }
             }
+        }
 
-            // Apply any incoming Actions as a result of input handling.
-            self.executor.process(
-                &mut self.world,
-                &mut self.scene_manager,
-                &mut self.event_manager,
-            );
+        // Input event handling.
+        let mut inputs = vec![];
+        loop {
+            match self.event_pump.poll() {
+                UserInput {
+                    event: Some(user_input::Event::NoEvent(..)),
+                } => break,
+                input => inputs.push(input),
+            }
+        }
+        let inputs = match &mut self.session {
+            Session::Live => inputs,
+            Session::Recording(recorder) => {
+                for input in &inputs {
+                    recorder.record_input(input);
+                }
+                inputs
+            }
+            Session::Replaying(replayer) => {
+                // Closing the window still quits a replay.
+                if inputs
+                    .iter()
+                    .any(|input| matches!(input.event, Some(user_input::Event::QuitEvent(..))))
+                {
+                    return false;
+                }
+                replayer.inputs().to_vec()
+            }
+        };
+        for input in inputs {
+            match input.event {
+                Some(user_input::Event::QuitEvent(..)) => {
+                    return false;
+                }
+                Some(user_input::Event::KeyEvent(event)) if event.key == "Q" => return false,
+                Some(user_input::Event::KeyEvent(event)) => {
+                    self.input_manager.handle(UserInput {
+                        event: Some(user_input::Event::KeyEvent(event)),
+                    });
+                }
+                // Some(event) => println!("{:#?}", event),
+                _ => {}
+            }
+        }
+
+        // Apply any incoming Actions as a result of input handling.
+        self.process_actions();
+
+        self.reload_assets(texture_manager);
+        self.preload_assets(texture_creator, texture_manager);
 
-            // Update time.
-            let curr_time = SystemTime::now();
-            let time_since_last_frame = curr_time.duration_since(prev_time).unwrap();
-            *self.world.write_resource() = time_since_last_frame;
-            self.fps_counter.progress(time_since_last_frame);
-            prev_time = curr_time;
+        // Update time.
+        let curr_time = self.clock.now();
+        let prev_time = self.prev_time.unwrap_or(curr_time);
+        let time_since_last_frame = match &self.session {
+            Session::Replaying(replayer) => replayer.delta(),
+            _ => curr_time - prev_time,
+        };
+        self.fps_counter.progress(curr_time - prev_time);
+        self.prev_time = Some(curr_time);
 
-            dispatcher.dispatch(&mut self.world);
+        // Simulation advances in fixed steps so that its results do not
+        // depend on the frame rate.
+        for _ in 0..self.timestep.advance(time_since_last_frame) {
+            let step = self
+                .world
+                .read_resource::<TimeScale>()
+                .apply(self.timestep.step());
+            *self.world.write_resource() = step;
+            self.dispatcher.dispatch(&mut self.world);
 
             // Apply any incoming Actions as a result of systems being dispatched.
-            self.executor.process(
-                &mut self.world,
-                &mut self.scene_manager,
-                &mut self.event_manager,
-            );
+            self.process_actions();
 
             self.world.maintain();
-            self.render(&mut texture_manager);
+        }
+
+        match &mut self.session {
+            Session::Live => {}
+            Session::Recording(recorder) => {
+                let checksum = positions_checksum(&self.world);
+                if let Err(e) = recorder.end_frame(time_since_last_frame, checksum) {
+                    error!("Recording stopped: {e}");
+                    self.session = Session::Live;
+                }
+            }
+            Session::Replaying(replayer) => {
+                if let Err(e) = replayer.verify(positions_checksum(&self.world)) {
+                    warn!("{e}");
+                }
+            }
+        }
 
-            self.fps_counter.end_frame();
+        self.world.write_resource::<Interpolation>().alpha = self.timestep.alpha();
+        self.render(texture_manager);
+        self.trim_textures(texture_manager);
+
+        self.fps_counter.end_frame();
+        *self.world.write_resource() = self.fps_counter.stats();
+
+        // Quit is handled at the end of the frame so that actions received
+        // together with it, e.g. a screenshot, still take effect.
+        if self.executor.quit_requested() {
+            return false;
+        }
+
+        if let Some(frame_interval) = self.frame_interval {
+            let frame_time = self.clock.now() - curr_time;
+            if frame_time < frame_interval {
+                std::thread::sleep(frame_interval - frame_time);
+            }
         }
+
+        true
     }
 
     pub fn halt(&self) {}
 
+    /// Executes pending Actions. During a replay the Actions received are
+    /// dropped and the recorded ones are executed instead.
+    fn process_actions(&mut self) {
+        match &mut self.session {
+            Session::Replaying(replayer) => {
+                while self.executor.next_action().is_some() {}
+                for action in replayer.next_actions() {
+                    self.executor.execute(
+                        action,
+                        &mut self.world,
+                        &mut self.scene_manager,
+                        &mut self.event_manager,
+                    );
+                }
+            }
+            _ => {
+                let mut actions = vec![];
+                while let Some(action) = self.executor.next_action() {
+                    actions.push(action.clone());
+                    self.executor.execute(
+                        action,
+                        &mut self.world,
+                        &mut self.scene_manager,
+                        &mut self.event_manager,
+                    );
+                }
+                if let Session::Recording(recorder) = &mut self.session {
+                    recorder.record_actions(&actions);
+                }
+            }
+        }
+        self.apply_window_changes();
+    }
+
+    /// Reloads assets that changed on disk while in hot reload mode.
+    fn reload_assets(&mut self, texture_manager: &mut TextureManager<sdl2::video::WindowContext>) {
+        let changes = match &mut self.asset_watcher {
+            Some(watcher) => watcher.poll(self.clock.now()),
+            None => return,
+        };
+
+        for change in changes {
+            let reloaded = match change.extension.as_str() {
+                "png" => texture_manager
+                    .reload(change.resource.as_str())
+                    .map(|reloaded| match reloaded {
+                        true => vec![AssetType::Texture],
+                        false => vec![],
+                    }),
+                "json" | "tmx" | "tsx" => self.reload_data(&change.resource),
+                _ => Ok(vec![]),
+            };
+
+            match reloaded {
+                Ok(asset_types) => {
+                    for asset_type in asset_types {
+                        info!("reloaded '{}'", &change.resource);
+                        self.event_manager.handle(Event {
+                            event_id: change.resource.clone(),
+                            event: Some(event::Event::AssetReloaded(AssetEvent {
+                                resource: change.resource.clone(),
+                                asset_type: asset_type as i32,
+                            })),
+                        });
+                    }
+                }
+                Err(e) => error!("Failed to reload '{}': {e}", &change.resource),
+            }
+        }
+    }
+
+    /// Starts requested preloads and caches the resources that finished
+    /// loading in the background, reporting progress to the host.
+    fn preload_assets<'l>(
+        &mut self,
+        texture_creator: &'l TextureCreator<WindowContext>,
+        texture_manager: &mut TextureManager<'l, WindowContext>,
+    ) {
+        let pending = std::mem::take(&mut self.world.write_resource::<PendingPreloads>().0);
+        for preload_action in pending {
+            self.preloads.push(Preload::start(
+                Arc::clone(&self.asset_source),
+                preload_action,
+            ));
+        }
+
+        for preload in &mut self.preloads {
+            while let Some(preloaded) = preload.next_preloaded() {
+                let resource = match preloaded {
+                    Preloaded::Sprite(resource, sprite) => {
+                        self.world
+                            .write_resource::<SpriteManager>()
+                            .insert(resource.as_str(), sprite);
+                        resource
+                    }
+                    Preloaded::TileMap(resource, map, tilesets) => {
+                        self.scene_manager
+                            .insert_tile_map(&resource, map, tilesets, &self.world);
+                        resource
+                    }
+                    Preloaded::Texture(resource, mut image) => {
+                        let texture = image.surface().and_then(|surface| {
+                            texture_creator
+                                .create_texture_from_surface(&surface)
+                                .map_err(|e| Status::internal(e.to_string()))
+                        });
+                        match texture {
+                            Ok(texture) => texture_manager.insert(resource.as_str(), texture),
+                            Err(e) => {
+                                warn!("Failed to preload texture '{resource}': {e}");
+                                preload.failed.push(resource.clone());
+                            }
+                        }
+                        resource
+                    }
+                    Preloaded::Failed(resource, e) => {
+                        warn!("Failed to preload '{resource}': {e}");
+                        resource
+                    }
+                };
+
+                self.event_manager.handle(preload_event(
+                    preload,
+                    resource,
+                    event::Event::PreloadProgress,
+                ));
+            }
+
+            if preload.done() {
+                self.event_manager.handle(preload_event(
+                    preload,
+                    String::default(),
+                    event::Event::PreloadDone,
+                ));
+            }
+        }
+        self.preloads.retain(|preload| !preload.done());
+    }
+
+    /// Reloads a json asset that may be a sprite sheet used by scene nodes or
+    /// a tile map or tileset used by the scene.
+    fn reload_data(&mut self, resource: &str) -> Result<Vec<AssetType>, Status> {
+        let reloaded = self.scene_manager.reload(resource, &mut self.world)?;
+        if reloaded.contains(&AssetType::Sprite) {
+            refresh_sprite_nodes(&self.world, resource);
+        }
+        Ok(reloaded)
+    }
+
+    fn apply_window_changes(&mut self) {
+        let changes = std::mem::take(&mut self.world.write_resource::<PendingWindowChanges>().0);
+
+        let window = self.canvas.window_mut();
+        for change in changes {
+            let result = match change {
+                WindowChange::Fullscreen(mode) => window.set_fullscreen(match mode {
+                    FullscreenMode::Windowed => FullscreenType::Off,
+                    FullscreenMode::Fullscreen => FullscreenType::True,
+                    FullscreenMode::Borderless => FullscreenType::Desktop,
+                }),
+                WindowChange::Resize(width, height) => {
+                    window.set_size(width, height).map_err(|e| e.to_string())
+                }
+            };
+            if let Err(e) = result {
+                error!("Failed to apply window change: {e}");
+            }
+        }
+    }
+
+    /// Unloads textures that are no longer needed and evicts the least
+    /// recently used ones if the texture budget is exceeded.
+    fn trim_textures(&mut self, texture_manager: &mut TextureManager<sdl2::video::WindowContext>) {
+        let unloads = std::mem::take(&mut self.world.write_resource::<PendingTextureUnloads>().0);
+        for texture_id in unloads {
+            if let Err(e) = texture_manager.unload(texture_id.as_str()) {
+                warn!("{e}");
+            }
+        }
+        texture_manager.end_frame();
+        *self.world.write_resource() = TextureStats(texture_manager.stats());
+    }
+
     fn render(&mut self, texture_manager: &mut TextureManager<sdl2::video::WindowContext>) {
         if let Err(e) = render(
             &mut self.canvas,
@@ -209,7 +616,224 @@ impl Core {
             texture_manager,
             self.world.system_data(),
         ) {
-            println!("{}", e);
+            error!("{}", e);
         }
     }
 }
+
+fn preload_event(
+    preload: &Preload,
+    resource: String,
+    event: fn(PreloadEvent) -> event::Event,
+) -> Event {
+    Event {
+        event_id: preload.event_id.clone(),
+        event: Some(event(PreloadEvent {
+            resource,
+            processed: preload.processed,
+            total: preload.total,
+            failed: preload.failed.clone(),
+        })),
+    }
+}
+
+// Assets are read from the pack archive if one is configured, or else from
+// loose files under the assets path, with override directories on top.
+fn open_asset_source(config: &CrustConfig) -> Result<Arc<dyn AssetSource>, Status> {
+    let base: Arc<dyn AssetSource> = match config.asset_pack.is_empty() {
+        true => Arc::new(DirectorySource::new(&config.assets_path)),
+        false => Arc::new(PackSource::open(&config.asset_pack)?),
+    };
+    if config.asset_overrides.is_empty() {
+        return Ok(base);
+    }
+
+    let mut layers: Vec<Arc<dyn AssetSource>> = config
+        .asset_overrides
+        .iter()
+        .map(|path| Arc::new(DirectorySource::new(path)) as Arc<dyn AssetSource>)
+        .collect();
+    layers.push(base);
+    Ok(Arc::new(LayeredSource::new(layers)))
+}
+
+fn logical_size(window_config: &Window) -> Rect {
+    match window_config.logical_width > 0 && window_config.logical_height > 0 {
+        true => Rect::new(
+            0,
+            0,
+            window_config.logical_width as u32,
+            window_config.logical_height as u32,
+        ),
+        false => Rect::new(
+            0,
+            0,
+            window_config.width as u32,
+            window_config.height as u32,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::ManualClock,
+        crust::{
+            action, shape, Action, Animation as AnimationPart, AnimationScript,
+            AnimationScriptAction, RectangleShape, RgBa, SceneNode, SceneNodeAction,
+            ScreenshotAction, Shape, Vector, VectorAnimation,
+        },
+    };
+    use sdl2::{image::LoadSurface, pixels::PixelFormatEnum, rect::Point, surface::Surface};
+
+    fn push(action: action::Action) {
+        ACTION_QUEUE.with(|queue| {
+            if let Some(queue) = &*queue.borrow() {
+                queue.push(Action {
+                    action: Some(action),
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn run_frames_steps_the_simulation() {
+        let clock = ManualClock::new();
+        let mut core = Core::init_with_clock(
+            CrustConfig {
+                headless: true,
+                window: Some(Window {
+                    title: "test".to_owned(),
+                    width: 64,
+                    height: 64,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            Arc::new(clock.clone()),
+        )
+        .unwrap();
+
+        push(action::Action::CreateSceneNode(SceneNodeAction {
+            scene_node: Some(SceneNode {
+                id: "box".to_owned(),
+                position: Some(Vector::default()),
+                rigid_body: true,
+                shape: Some(Shape {
+                    shape: Some(shape::Shape::Rectangle(RectangleShape {
+                        width: 4,
+                        height: 4,
+                    })),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        }));
+        push(action::Action::PlayAnimation(AnimationScriptAction {
+            script: Some(AnimationScript {
+                id: "move_right".to_owned(),
+                animation: vec![AnimationPart {
+                    translation: Some(VectorAnimation {
+                        vec: Some(Vector {
+                            x: 2.0,
+                            ..Default::default()
+                        }),
+                        delay: 10,
+                        repeat: 3,
+                    }),
+                    ..Default::default()
+                }],
+                repeat: 1,
+            }),
+            scene_node_id: "box".to_owned(),
+            ..Default::default()
+        }));
+        let position = |core: &Core| {
+            let positions = core.world.read_storage::<Position>();
+            let animations = core.world.read_storage::<Animation>();
+            let (position, animation) = (&positions, animations.maybe()).join().next().unwrap();
+            (position.0.top_left(), animation.is_some())
+        };
+
+        // The first frame creates the node and only starts the clock.
+        assert!(core.run_frames(1));
+        assert_eq!(position(&core), (Point::new(0, 0), true));
+
+        // Each frame advances the clock by one 10ms simulation step.
+        for _ in 0..2 {
+            clock.advance(Duration::from_millis(10));
+            assert!(core.run_frames(1));
+        }
+        assert_eq!(position(&core), (Point::new(4, 0), true));
+
+        for _ in 0..2 {
+            clock.advance(Duration::from_millis(10));
+            assert!(core.run_frames(1));
+        }
+        assert_eq!(position(&core), (Point::new(6, 0), false));
+    }
+
+    #[test]
+    fn screenshots_include_the_letterbox_bars() {
+        let filename = std::env::temp_dir().join(format!(
+            "crust_letterboxed_screenshot_{}.png",
+            std::process::id()
+        ));
+        // The 32x32 frame is scaled to 48x48 and centred in the window.
+        let mut core = Core::init(CrustConfig {
+            headless: true,
+            window: Some(Window {
+                title: "test".to_owned(),
+                width: 64,
+                height: 48,
+                logical_width: 32,
+                logical_height: 32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+
+        push(action::Action::CreateSceneNode(SceneNodeAction {
+            scene_node: Some(SceneNode {
+                id: "background".to_owned(),
+                position: Some(Vector::default()),
+                shape: Some(Shape {
+                    shape: Some(shape::Shape::Rectangle(RectangleShape {
+                        width: 32,
+                        height: 32,
+                    })),
+                    colour: Some(RgBa {
+                        red: 255,
+                        green: 255,
+                        blue: 255,
+                        alpha: 255,
+                    }),
+                    filled: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        }));
+        push(action::Action::Screenshot(ScreenshotAction {
+            filename: filename.to_str().unwrap().to_owned(),
+        }));
+        assert!(core.run_frames(2));
+
+        let surface = Surface::from_file(&filename)
+            .unwrap()
+            .convert_format(PixelFormatEnum::RGBA32)
+            .unwrap();
+        assert_eq!((surface.width(), surface.height()), (64, 48));
+        let pitch = surface.pitch() as usize;
+        let pixel = |x: usize, y: usize| {
+            surface.with_lock(|pixels| pixels[y * pitch + x * 4..][..4].to_vec())
+        };
+        assert_eq!(pixel(2, 24), vec![0, 0, 0, 255]);
+        assert_eq!(pixel(32, 24), vec![255, 255, 255, 255]);
+        assert_eq!(pixel(61, 47), vec![0, 0, 0, 255]);
+
+        std::fs::remove_file(&filename).unwrap();
+    }
+}
@@ -2,15 +2,18 @@ use crate::core::Status;
 use crate::crust::{self, UserInput};
 use sdl2::event::Event;
 use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
 
 pub struct EventPump {
     event_pump: sdl2::EventPump,
+    logical_size: Rect,
 }
 
 impl EventPump {
-    pub fn new(sdl_context: &sdl2::Sdl) -> Result<Self, Status> {
+    pub fn new(sdl_context: &sdl2::Sdl, logical_size: Rect) -> Result<Self, Status> {
         Ok(EventPump {
             event_pump: sdl_context.event_pump()?,
+            logical_size,
         })
     }
 
@@ -55,11 +58,7 @@ impl EventPump {
                 x, y, xrel, yrel, ..
             } => UserInput {
                 event: Some(crust::user_input::Event::MouseEvent(crust::MouseEvent {
-                    absolute_position: Some(crust::Vector {
-                        x: x as f64,
-                        y: y as f64,
-                        z: 0.0,
-                    }),
+                    absolute_position: Some(self.logical_position(x, y)),
                     relative_position: Some(crust::Vector {
                         x: xrel as f64,
                         y: yrel as f64,
@@ -74,11 +73,7 @@ impl EventPump {
                 event: Some(crust::user_input::Event::MouseEvent(crust::MouseEvent {
                     button: translate_mouse_button(mouse_btn),
                     key_state: crust::KeyState::Pressed as i32,
-                    absolute_position: Some(crust::Vector {
-                        x: x as f64,
-                        y: y as f64,
-                        z: 0.0,
-                    }),
+                    absolute_position: Some(self.logical_position(x, y)),
                     ..Default::default()
                 })),
             },
@@ -88,11 +83,7 @@ impl EventPump {
                 event: Some(crust::user_input::Event::MouseEvent(crust::MouseEvent {
                     button: translate_mouse_button(mouse_btn),
                     key_state: crust::KeyState::Released as i32,
-                    absolute_position: Some(crust::Vector {
-                        x: x as f64,
-                        y: y as f64,
-                        z: 0.0,
-                    }),
+                    absolute_position: Some(self.logical_position(x, y)),
                     ..Default::default()
                 })),
             },
@@ -103,6 +94,21 @@ impl EventPump {
     }
 }
 
+impl EventPump {
+    /// Returns the mouse position in the logical space the game is rendered at.
+    ///
+    /// SDL already maps mouse coordinates from window to logical space when a
+    /// logical size is set on the renderer. Positions on the letterbox bars
+    /// however fall outside the logical area, so they are clamped to its edges.
+    fn logical_position(&self, x: i32, y: i32) -> crust::Vector {
+        crust::Vector {
+            x: x.clamp(0, self.logical_size.width() as i32 - 1) as f64,
+            y: y.clamp(0, self.logical_size.height() as i32 - 1) as f64,
+            z: 0.0,
+        }
+    }
+}
+
 fn translate_mouse_button(mouse_btn: MouseButton) -> String {
     match mouse_btn {
         MouseButton::Left => String::from("Left"),
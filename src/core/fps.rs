@@ -1,42 +1,48 @@
-use std::time::{Duration, SystemTime};
+use super::Clock;
+use log::debug;
+use std::{sync::Arc, time::Duration};
+
+/// Frame timing statistics over the last full second.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FrameStats {
+    pub fps: u32,
+    pub best_frame_duration: Duration,
+    pub worst_frame_duration: Duration,
+}
 
 pub struct FpsCounter {
     pub fps: u32,
     frame_count: u32,
     elapsed_time: Duration,
 
-    frame_started: SystemTime,
+    clock: Arc<dyn Clock>,
+    frame_started: Duration,
     best_frame_duration: Duration,
     worst_frame_duration: Duration,
+    stats: FrameStats,
 }
 
-impl Default for FpsCounter {
-    fn default() -> Self {
-        Self {
+impl FpsCounter {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        FpsCounter {
             fps: Default::default(),
             frame_count: Default::default(),
             elapsed_time: Default::default(),
-            frame_started: SystemTime::now(),
+            frame_started: clock.now(),
+            clock,
             best_frame_duration: Default::default(),
             worst_frame_duration: Default::default(),
+            stats: Default::default(),
         }
     }
-}
-
-impl FpsCounter {
-    pub fn new() -> Self {
-        FpsCounter::default()
-    }
 
     pub fn start_frame(&mut self) {
-        self.frame_started = SystemTime::now();
+        self.frame_started = self.clock.now();
     }
     pub fn end_frame(&mut self) {
         self.frame_count += 1;
 
-        let frame_duration = SystemTime::now()
-            .duration_since(self.frame_started)
-            .unwrap();
+        let frame_duration = self.clock.now().saturating_sub(self.frame_started);
 
         if self.best_frame_duration == Duration::ZERO || frame_duration < self.best_frame_duration {
             self.best_frame_duration = frame_duration;
@@ -50,20 +56,57 @@ impl FpsCounter {
             self.frame_count = 0;
             self.elapsed_time -= Duration::from_millis(1000);
 
-            println!(
-                "🦀 fps: {} (avg: {:.2} msec, best: {:.2} msec, worst: {:.2} msec",
+            debug!(
+                "fps: {} (avg: {:.2} msec, best: {:.2} msec, worst: {:.2} msec",
                 self.fps,
                 1000.0 / self.fps as f32,
                 self.best_frame_duration.as_millis(),
                 self.worst_frame_duration.as_millis(),
             );
 
+            self.stats = FrameStats {
+                fps: self.fps,
+                best_frame_duration: self.best_frame_duration,
+                worst_frame_duration: self.worst_frame_duration,
+            };
             self.best_frame_duration = Duration::ZERO;
             self.worst_frame_duration = Duration::ZERO;
         }
     }
 
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
     pub fn progress(&mut self, time_since_last_frame: Duration) {
         self.elapsed_time += time_since_last_frame;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ManualClock;
+
+    #[test]
+    fn stats_over_one_second() {
+        let clock = ManualClock::new();
+        let mut fps_counter = FpsCounter::new(Arc::new(clock.clone()));
+
+        for i in 0..50 {
+            fps_counter.start_frame();
+            let frame_duration = Duration::from_millis(match i % 2 {
+                0 => 15,
+                _ => 25,
+            });
+            clock.advance(frame_duration);
+            fps_counter.progress(frame_duration);
+            fps_counter.end_frame();
+        }
+
+        let stats = fps_counter.stats();
+        assert_eq!(stats.fps, 50);
+        assert_eq!(stats.best_frame_duration, Duration::from_millis(15));
+        assert_eq!(stats.worst_frame_duration, Duration::from_millis(25));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    components::{Position, Scaling, SpriteInfo},
+    resources::SpriteManager,
+};
+use specs::prelude::*;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// Minimum time between two scans of the assets directories.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An asset file that was modified since the last scan.
+#[derive(Debug, PartialEq)]
+pub struct AssetChange {
+    // Path relative to the assets directory without the file extension, as
+    // used for keys in resource managers.
+    pub resource: String,
+    pub extension: String,
+}
+
+/// Watches the assets directories, i.e. the assets path and any override
+/// directories, for modified files by polling their mtimes.
+pub struct AssetWatcher {
+    roots: Vec<PathBuf>,
+    mtimes: HashMap<PathBuf, SystemTime>,
+    last_poll: Option<Duration>,
+}
+
+impl AssetWatcher {
+    pub fn new(roots: &[String]) -> Self {
+        let mut watcher = AssetWatcher {
+            roots: roots.iter().map(PathBuf::from).collect(),
+            mtimes: HashMap::new(),
+            last_poll: None,
+        };
+        watcher.scan();
+        watcher
+    }
+
+    /// Returns the assets that changed since the last scan. Scans at most
+    /// once every POLL_INTERVAL, where `now` is the current time of the
+    /// engine's clock.
+    pub fn poll(&mut self, now: Duration) -> Vec<AssetChange> {
+        match self.last_poll {
+            Some(last_poll) if now < last_poll + POLL_INTERVAL => return vec![],
+            _ => self.last_poll = Some(now),
+        }
+        self.scan()
+    }
+
+    fn scan(&mut self) -> Vec<AssetChange> {
+        let mut files = vec![];
+        for root in &self.roots {
+            collect_files(root, &mut files);
+        }
+
+        let mut changes = vec![];
+        for (path, mtime) in files {
+            let prev_mtime = self.mtimes.insert(path.clone(), mtime);
+            if prev_mtime.is_none_or(|prev_mtime| prev_mtime == mtime) {
+                continue;
+            }
+            if let Some(change) = self.make_change(&path) {
+                changes.push(change);
+            }
+        }
+        changes
+    }
+
+    fn make_change(&self, path: &Path) -> Option<AssetChange> {
+        let relative = self
+            .roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())?;
+        let extension = relative.extension()?.to_string_lossy().into_owned();
+        let resource = relative.with_extension("");
+        Some(AssetChange {
+            resource: resource.to_string_lossy().replace('\\', "/"),
+            extension,
+        })
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, SystemTime)>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => collect_files(&path, files),
+            Ok(metadata) => {
+                if let Ok(mtime) = metadata.modified() {
+                    files.push((path, mtime));
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Updates the frame of live scene nodes that use the reloaded sprite sheet
+/// `resource`, as its frames may have been resized or removed.
+pub fn refresh_sprite_nodes(world: &World, resource: &str) {
+    let sprite_manager = world.read_resource::<SpriteManager>();
+    let sprite = match sprite_manager.get(resource) {
+        Some(sprite) if !sprite.frames.is_empty() => sprite,
+        _ => return,
+    };
+
+    let mut sprite_infos = world.write_storage::<SpriteInfo>();
+    let mut positions = world.write_storage::<Position>();
+    let scalings = world.read_storage::<Scaling>();
+
+    for (sprite_info, position, scaling) in
+        (&mut sprite_infos, &mut positions, (&scalings).maybe()).join()
+    {
+        if sprite_info.sprite_id != resource {
+            continue;
+        }
+
+        sprite_info.frame_index = sprite_info.frame_index.min(sprite.frames.len() - 1);
+        sprite_info.bounding_box = sprite.frames[sprite_info.frame_index].bounding_box;
+
+        let (scale_x, scale_y) = scaling.map_or((1.0, 1.0), |scaling| scaling.0);
+        position.0.resize(
+            (sprite_info.bounding_box.width() as f64 * scale_x) as u32,
+            (sprite_info.bounding_box.height() as f64 * scale_y) as u32,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn reports_modified_assets() {
+        let root = std::env::temp_dir().join(format!("crust_hot_reload_{}", std::process::id()));
+        let assets = root.join("assets");
+        let overrides = root.join("mod");
+        std::fs::create_dir_all(assets.join("sprites")).unwrap();
+        std::fs::create_dir_all(overrides.join("maps")).unwrap();
+        std::fs::write(assets.join("sprites/hero.json"), "{}").unwrap();
+        std::fs::write(overrides.join("maps/level1.json"), "{}").unwrap();
+
+        let mut watcher = AssetWatcher::new(&[
+            overrides.to_str().unwrap().to_owned(),
+            assets.to_str().unwrap().to_owned(),
+        ]);
+        assert_eq!(watcher.poll(Duration::ZERO), vec![]);
+
+        for path in [
+            assets.join("sprites/hero.json"),
+            overrides.join("maps/level1.json"),
+        ] {
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() + Duration::from_secs(10))
+                .unwrap();
+        }
+
+        // Scans are throttled.
+        assert_eq!(watcher.poll(Duration::from_millis(100)), vec![]);
+        let mut changes = watcher.poll(Duration::from_millis(600));
+        changes.sort_by(|a, b| a.resource.cmp(&b.resource));
+        assert_eq!(
+            changes,
+            vec![
+                AssetChange {
+                    resource: "maps/level1".to_owned(),
+                    extension: "json".to_owned(),
+                },
+                AssetChange {
+                    resource: "sprites/hero".to_owned(),
+                    extension: "json".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(watcher.poll(Duration::from_millis(1200)), vec![]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
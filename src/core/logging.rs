@@ -0,0 +1,184 @@
+use crate::crust::{LogLevel, LogRecord, Logging};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+pub type LogHandler = Arc<dyn Fn(&LogRecord) + Send + Sync>;
+
+static LOGGER: Logger = Logger {
+    filter: Mutex::new(LogFilter {
+        level: LevelFilter::Info,
+        modules: vec![],
+    }),
+    handler: Mutex::new(None),
+    frame: AtomicU64::new(0),
+};
+
+/// Installs the engine logger configured by `config`. Can be called again to
+/// reconfigure it, e.g. when the engine is re-initialised.
+pub fn init_logging(config: &Logging) {
+    let filter = LogFilter::new(config);
+    log::set_max_level(filter.max_level());
+    *LOGGER.filter.lock().unwrap() = filter;
+    LOGGER.frame.store(0, Ordering::Relaxed);
+
+    // The logger is only installed once per process.
+    let _ = log::set_logger(&LOGGER);
+}
+
+/// Routes log records to `handler` instead of the console. It replaces any
+/// previously registered handler.
+pub fn register_log_handler(handler: LogHandler) {
+    *LOGGER.handler.lock().unwrap() = Some(handler);
+}
+
+/// Advances the frame that is attached to log records.
+pub fn next_log_frame() {
+    LOGGER.frame.fetch_add(1, Ordering::Relaxed);
+}
+
+struct Logger {
+    filter: Mutex<LogFilter>,
+    handler: Mutex<Option<LogHandler>>,
+    frame: AtomicU64,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.lock().unwrap().level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let frame = self.frame.load(Ordering::Relaxed);
+        // The handler is called without holding the lock, in case it logs.
+        let handler = match self.handler.lock().unwrap().clone() {
+            Some(handler) => handler,
+            None => {
+                let line = format!(
+                    "🦀 [{} {}] {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                );
+                match record.level() {
+                    Level::Error | Level::Warn => eprintln!("{line}"),
+                    _ => println!("{line}"),
+                }
+                return;
+            }
+        };
+
+        let log_record = LogRecord {
+            level: to_log_level(record.level()) as i32,
+            module: record.target().to_owned(),
+            message: record.args().to_string(),
+            frame,
+        };
+        handler(&log_record);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Minimum severity of records per module.
+struct LogFilter {
+    level: LevelFilter,
+
+    // Module path prefixes with their level, longest prefix first.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    fn new(config: &Logging) -> Self {
+        let mut modules: Vec<_> = config
+            .modules
+            .iter()
+            .map(|(module, level)| (module.clone(), to_level_filter(*level)))
+            .collect();
+        modules.sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+
+        LogFilter {
+            level: to_level_filter(config.level),
+            modules,
+        }
+    }
+
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(module, _)| target.starts_with(module.as_str()))
+            .map_or(self.level, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, |max, level| max.max(level))
+    }
+}
+
+fn to_level_filter(level: i32) -> LevelFilter {
+    match LogLevel::from_i32(level) {
+        Some(LogLevel::Off) => LevelFilter::Off,
+        Some(LogLevel::Error) => LevelFilter::Error,
+        Some(LogLevel::Warn) => LevelFilter::Warn,
+        Some(LogLevel::Info | LogLevel::Unspecified) | None => LevelFilter::Info,
+        Some(LogLevel::Debug) => LevelFilter::Debug,
+        Some(LogLevel::Trace) => LevelFilter::Trace,
+    }
+}
+
+fn to_log_level(level: Level) -> LogLevel {
+    match level {
+        Level::Error => LogLevel::Error,
+        Level::Warn => LogLevel::Warn,
+        Level::Info => LogLevel::Info,
+        Level::Debug => LogLevel::Debug,
+        Level::Trace => LogLevel::Trace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_levels_override_default() {
+        let filter = LogFilter::new(&Logging {
+            level: LogLevel::Warn as i32,
+            modules: [
+                ("crust_lib::scene".to_owned(), LogLevel::Debug as i32),
+                (
+                    "crust_lib::scene::scene_builder".to_owned(),
+                    LogLevel::Off as i32,
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        });
+
+        assert_eq!(filter.level("crust_lib::core::fps"), LevelFilter::Warn);
+        assert_eq!(
+            filter.level("crust_lib::scene::scene_manager"),
+            LevelFilter::Debug
+        );
+        assert_eq!(
+            filter.level("crust_lib::scene::scene_builder"),
+            LevelFilter::Off
+        );
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn unspecified_level_defaults_to_info() {
+        let filter = LogFilter::new(&Logging::default());
+        assert_eq!(filter.level("crust_lib::core::fps"), LevelFilter::Info);
+    }
+}
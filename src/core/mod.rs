@@ -1,9 +1,19 @@
+mod clock;
 mod core;
 mod events;
 mod fps;
+mod hot_reload;
+mod logging;
+mod recording;
 mod status;
+mod timestep;
 
 pub use self::core::Core;
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use events::EventPump;
-pub use fps::FpsCounter;
+pub use fps::{FpsCounter, FrameStats};
+pub use hot_reload::{refresh_sprite_nodes, AssetChange, AssetWatcher};
+pub use logging::{init_logging, next_log_frame, register_log_handler, LogHandler};
+pub use recording::{positions_checksum, Recorder, Replayer, Session};
 pub use status::Status;
+pub use timestep::FixedTimestep;
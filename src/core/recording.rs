@@ -0,0 +1,236 @@
+use super::Status;
+use crate::{
+    components::Position,
+    crust::{Action, ActionBatch, RecordedFrame, UserInput},
+};
+use log::error;
+use prost::Message;
+use specs::prelude::*;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    time::Duration,
+};
+
+/// Whether the session is recorded, replayed or neither.
+pub enum Session {
+    Live,
+    Recording(Recorder),
+    Replaying(Replayer),
+}
+
+/// Writes user input, actions and frame timing of a session to a file.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    frame: RecordedFrame,
+}
+
+impl Recorder {
+    pub fn create(filename: &str) -> Result<Self, Status> {
+        Ok(Recorder {
+            writer: BufWriter::new(File::create(filename)?),
+            frame: RecordedFrame::default(),
+        })
+    }
+
+    pub fn record_input(&mut self, input: &UserInput) {
+        self.frame.input.push(input.clone());
+    }
+
+    pub fn record_actions(&mut self, actions: &[Action]) {
+        self.frame.batch.push(ActionBatch {
+            action: actions.to_vec(),
+        });
+    }
+
+    pub fn end_frame(&mut self, delta: Duration, checksum: u64) -> Result<(), Status> {
+        self.frame.delta = delta.as_micros() as u64;
+        self.frame.checksum = checksum;
+
+        let mut buffer = vec![];
+        self.frame
+            .encode_length_delimited(&mut buffer)
+            .map_err(|e| Status::new("Failed to encode recorded frame", e))?;
+        self.writer.write_all(&buffer)?;
+
+        self.frame = RecordedFrame {
+            frame: self.frame.frame + 1,
+            ..Default::default()
+        };
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            error!("Failed to save recording: {e}");
+        }
+    }
+}
+
+/// Feeds back a recorded session frame by frame.
+pub struct Replayer {
+    data: Vec<u8>,
+    offset: usize,
+
+    frame: RecordedFrame,
+    next_batch: usize,
+}
+
+impl Replayer {
+    pub fn open(filename: &str) -> Result<Self, Status> {
+        Ok(Replayer {
+            data: std::fs::read(filename)?,
+            offset: 0,
+            frame: RecordedFrame::default(),
+            next_batch: 0,
+        })
+    }
+
+    /// Moves to the next recorded frame. Returns false when the recording
+    /// has ended.
+    pub fn next_frame(&mut self) -> Result<bool, Status> {
+        let mut buffer = &self.data[self.offset..];
+        if buffer.is_empty() {
+            return Ok(false);
+        }
+
+        let remaining = buffer.len();
+        self.frame = RecordedFrame::decode_length_delimited(&mut buffer)
+            .map_err(|e| Status::new("Failed to decode recorded frame", e))?;
+        self.offset += remaining - buffer.len();
+        self.next_batch = 0;
+        Ok(true)
+    }
+
+    pub fn delta(&self) -> Duration {
+        Duration::from_micros(self.frame.delta)
+    }
+
+    pub fn inputs(&self) -> &[UserInput] {
+        &self.frame.input
+    }
+
+    /// Returns the actions of the next batch in the current frame.
+    pub fn next_actions(&mut self) -> Vec<Action> {
+        let actions = match self.frame.batch.get(self.next_batch) {
+            Some(batch) => batch.action.clone(),
+            None => vec![],
+        };
+        self.next_batch += 1;
+        actions
+    }
+
+    /// Checks that the world state at the end of the frame matches the
+    /// recording.
+    pub fn verify(&self, checksum: u64) -> Result<(), Status> {
+        match self.frame.checksum == checksum {
+            true => Ok(()),
+            false => Err(Status::internal(format!(
+                "Replay diverged from recording at frame {}",
+                self.frame.frame
+            ))),
+        }
+    }
+}
+
+/// Returns a checksum of the positions of all scene nodes, which is used to
+/// detect replays that diverge from their recording.
+pub fn positions_checksum(world: &World) -> u64 {
+    let entities = world.entities();
+    let positions = world.read_storage::<Position>();
+
+    // FNV-1a, which unlike the std hashers is stable across Rust versions.
+    let mut checksum: u64 = 0xcbf29ce484222325;
+    for (entity, position) in (&entities, &positions).join() {
+        for value in [
+            entity.id() as i32,
+            position.0.x(),
+            position.0.y(),
+            position.0.width() as i32,
+            position.0.height() as i32,
+        ] {
+            for byte in value.to_le_bytes() {
+                checksum ^= byte as u64;
+                checksum = checksum.wrapping_mul(0x100000001b3);
+            }
+        }
+    }
+    checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crust::{action, KeyEvent, NoopAction};
+    use sdl2::rect::Rect;
+
+    #[test]
+    fn replays_recorded_frames() {
+        let root = std::env::temp_dir().join(format!(
+            "crust_replays_recorded_frames_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&root).unwrap();
+        let filename = root.join("session.pb");
+        let filename = filename.to_str().unwrap();
+
+        let noop = Action {
+            action: Some(action::Action::Noop(NoopAction {})),
+        };
+        {
+            let mut recorder = Recorder::create(filename).unwrap();
+            recorder.record_input(&UserInput {
+                event: Some(crate::crust::user_input::Event::KeyEvent(KeyEvent {
+                    key: "Space".to_owned(),
+                    ..Default::default()
+                })),
+            });
+            recorder.record_actions(&[]);
+            recorder.record_actions(&[noop.clone(), noop.clone()]);
+            recorder.end_frame(Duration::from_millis(16), 42).unwrap();
+
+            recorder.record_actions(&[noop.clone()]);
+            recorder.end_frame(Duration::from_millis(17), 7).unwrap();
+        }
+
+        let mut replayer = Replayer::open(filename).unwrap();
+        assert_eq!(replayer.next_frame().unwrap(), true);
+        assert_eq!(replayer.delta(), Duration::from_millis(16));
+        assert_eq!(replayer.inputs().len(), 1);
+        assert_eq!(replayer.next_actions().len(), 0);
+        assert_eq!(replayer.next_actions().len(), 2);
+        assert_eq!(replayer.next_actions().len(), 0);
+        assert!(replayer.verify(42).is_ok());
+
+        assert_eq!(replayer.next_frame().unwrap(), true);
+        assert_eq!(replayer.delta(), Duration::from_millis(17));
+        assert_eq!(replayer.next_actions(), vec![noop]);
+        assert!(replayer.verify(42).is_err());
+
+        assert_eq!(replayer.next_frame().unwrap(), false);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn checksum_follows_positions() {
+        let mut world = World::new();
+        world.register::<Position>();
+        let entity = world
+            .create_entity()
+            .with(Position(Rect::new(0, 0, 16, 16)))
+            .build();
+
+        let checksum = positions_checksum(&world);
+        assert_eq!(positions_checksum(&world), checksum);
+
+        world
+            .write_storage::<Position>()
+            .get_mut(entity)
+            .unwrap()
+            .0
+            .offset(1, 0);
+        assert_ne!(positions_checksum(&world), checksum);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::crust::StatusCode;
 use std::{error::Error, fmt};
 
 #[derive(Debug)]
@@ -24,6 +25,22 @@ impl Status {
     pub fn not_found(msg: impl Into<String>) -> Self {
         Status::NotFound(msg.into())
     }
+
+    pub fn code(&self) -> StatusCode {
+        match self {
+            Status::Ok => StatusCode::Ok,
+            Status::Internal(_) => StatusCode::Internal,
+            Status::InvalidArgument(_) => StatusCode::InvalidArgument,
+            Status::NotFound(_) => StatusCode::NotFound,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Status::Ok => "",
+            Status::Internal(msg) | Status::InvalidArgument(msg) | Status::NotFound(msg) => msg,
+        }
+    }
 }
 
 impl From<std::io::Error> for Status {
@@ -0,0 +1,116 @@
+use super::Status;
+use crate::crust::Timing;
+use std::time::Duration;
+
+/// Accumulates elapsed frame time and splits it into fixed simulation steps.
+pub struct FixedTimestep {
+    step: Duration,
+    max_frame_time: Duration,
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    pub fn new(timing: &Timing) -> Result<Self, Status> {
+        let update_rate = match timing.update_rate {
+            0 => DEFAULT_UPDATE_RATE,
+            rate if rate > MAX_UPDATE_RATE => {
+                return Err(Status::invalid_argument(format!(
+                    "Update rate should be at most {MAX_UPDATE_RATE} but was {rate}"
+                )))
+            }
+            rate => rate,
+        };
+        let max_frame_time = match timing.max_frame_time {
+            0 => DEFAULT_MAX_FRAME_TIME,
+            msec => msec,
+        };
+
+        Ok(FixedTimestep {
+            step: Duration::from_secs(1) / update_rate,
+            max_frame_time: Duration::from_millis(max_frame_time as u64),
+            accumulator: Duration::ZERO,
+        })
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Adds the time of the last frame and returns the number of simulation
+    /// steps that need to run to catch up with it. Steps beyond
+    /// MAX_STEPS_PER_FRAME are dropped, like the time of long frames is.
+    pub fn advance(&mut self, frame_time: Duration) -> u32 {
+        self.accumulator += frame_time.min(self.max_frame_time);
+
+        let (accumulated, step) = (self.accumulator.as_nanos(), self.step.as_nanos());
+        self.accumulator = Duration::from_nanos((accumulated % step) as u64);
+        (accumulated / step).min(MAX_STEPS_PER_FRAME as u128) as u32
+    }
+
+    /// Fraction of a step that has accumulated since the last simulation step
+    /// in [0, 1). It is used for interpolating between the last two steps.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.step.as_secs_f64()
+    }
+}
+
+const DEFAULT_UPDATE_RATE: u32 = 100;
+const DEFAULT_MAX_FRAME_TIME: u32 = 250;
+
+// Steps shorter than a millisecond cost more than they gain.
+const MAX_UPDATE_RATE: u32 = 1000;
+const MAX_STEPS_PER_FRAME: u32 = 250;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestep() -> FixedTimestep {
+        FixedTimestep::new(&Timing {
+            update_rate: 100,
+            render_rate: 0,
+            max_frame_time: 50,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn accumulates_partial_steps() {
+        let mut timestep = timestep();
+        assert_eq!(timestep.step(), Duration::from_millis(10));
+
+        assert_eq!(timestep.advance(Duration::from_millis(4)), 0);
+        assert!((timestep.alpha() - 0.4).abs() < 1e-9);
+
+        assert_eq!(timestep.advance(Duration::from_millis(17)), 2);
+        assert!((timestep.alpha() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn defaults_for_unset_timing() {
+        let mut timestep = FixedTimestep::new(&Timing::default()).unwrap();
+        assert_eq!(timestep.step(), Duration::from_millis(10));
+        assert_eq!(timestep.advance(Duration::from_secs(1)), 25);
+    }
+
+    #[test]
+    fn clamps_long_frames() {
+        let mut timestep = timestep();
+        assert_eq!(timestep.advance(Duration::from_secs(3)), 5);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn limits_update_rate_and_steps_per_frame() {
+        let timing = |update_rate| Timing {
+            update_rate,
+            render_rate: 0,
+            max_frame_time: 10_000,
+        };
+        assert!(FixedTimestep::new(&timing(2_000_000_000)).is_err());
+
+        let mut timestep = FixedTimestep::new(&timing(1000)).unwrap();
+        assert_eq!(timestep.advance(Duration::from_secs(1)), 250);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+}
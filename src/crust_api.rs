@@ -1,8 +1,8 @@
 use crate::action::ACTION_QUEUE;
-use crate::core::Core;
-use crate::crust::{Action, CrustConfig, Event, UserInput};
+use crate::core::{Core, LogHandler};
+use crate::crust::{action, Action, CrustConfig, Event, LogRecord, ScreenshotAction, UserInput};
 use prost::Message;
-use std::cell::RefCell;
+use std::{cell::RefCell, sync::Arc};
 
 thread_local!(static CORE: RefCell<Option<Core>> = RefCell::new(None));
 
@@ -10,7 +10,6 @@ thread_local!(static CORE: RefCell<Option<Core>> = RefCell::new(None));
 pub extern "C" fn init(len: i64, encoded_action: *const u8) {
     let config: CrustConfig = decode_message(len, encoded_action);
 
-    println!("🦀 config: {:?}", config);
     CORE.with(|core| {
         *core.borrow_mut() = Some(Core::init(config).expect("Failed to init Core"));
     });
@@ -43,6 +42,21 @@ pub extern "C" fn execute(len: i64, encoded_action: *const u8) {
     });
 }
 
+/// Convenience for hosts that only need to capture frames, e.g. for golden
+/// image tests. Equivalent to executing a ScreenshotAction.
+#[no_mangle]
+pub extern "C" fn screenshot(len: i64, filename: *const u8) {
+    let filename = decode_string(len, filename);
+
+    ACTION_QUEUE.with(|queue| {
+        if let Some(queue) = &*queue.borrow() {
+            queue.push(Action {
+                action: Some(action::Action::Screenshot(ScreenshotAction { filename })),
+            });
+        }
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn register_input_handler(handler: extern "C" fn(usize, *const u8)) {
     CORE.with(|core| {
@@ -61,6 +75,14 @@ pub extern "C" fn register_event_handler(handler: extern "C" fn(usize, *const u8
     });
 }
 
+/// Log records are passed to |handler| encoded as LogRecord messages instead
+/// of being printed to the console. Registering a handler replaces the
+/// previous one.
+#[no_mangle]
+pub extern "C" fn register_log_handler(handler: extern "C" fn(usize, *const u8)) {
+    crate::core::register_log_handler(wrap_log_handler(handler));
+}
+
 fn decode_message<T: Message + Default>(len: i64, encoded_action: *const u8) -> T {
     let buffer: &[u8];
     unsafe {
@@ -69,6 +91,14 @@ fn decode_message<T: Message + Default>(len: i64, encoded_action: *const u8) ->
     T::decode(buffer).expect("🦀 Failed to parse protobuf message")
 }
 
+fn decode_string(len: i64, encoded: *const u8) -> String {
+    let buffer: &[u8];
+    unsafe {
+        buffer = std::slice::from_raw_parts(encoded, len as usize);
+    }
+    String::from_utf8_lossy(buffer).into_owned()
+}
+
 fn wrap_input_handler(handler: extern "C" fn(usize, *const u8)) -> Box<dyn Fn(&UserInput)> {
     Box::new(move |event: &UserInput| {
         let mut bytes = vec![];
@@ -88,3 +118,13 @@ fn wrap_event_handler(handler: extern "C" fn(usize, *const u8)) -> Box<dyn Fn(&E
         handler(bytes.len(), bytes.as_mut_ptr());
     })
 }
+
+fn wrap_log_handler(handler: extern "C" fn(usize, *const u8)) -> LogHandler {
+    Arc::new(move |record: &LogRecord| {
+        let mut bytes = vec![];
+        record
+            .encode(&mut bytes)
+            .expect("Failed to encode LogRecord message");
+        handler(bytes.len(), bytes.as_mut_ptr());
+    })
+}
@@ -1,6 +1,17 @@
 // Declare the modules created from protbufs.
 pub mod crust {
     tonic::include_proto!("crust");
+
+    impl From<&RgBa> for sdl2::pixels::Color {
+        fn from(rgba: &RgBa) -> Self {
+            sdl2::pixels::Color::RGBA(
+                rgba.red.clamp(0, 255) as u8,
+                rgba.green.clamp(0, 255) as u8,
+                rgba.blue.clamp(0, 255) as u8,
+                rgba.alpha.clamp(0, 255) as u8,
+            )
+        }
+    }
 }
 
 pub mod action;
@@ -10,6 +21,7 @@ pub mod core;
 pub mod crust_api;
 pub mod event;
 pub mod input;
+pub mod particles;
 pub mod physics;
 pub mod resources;
 pub mod scene;
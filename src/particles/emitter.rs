@@ -0,0 +1,363 @@
+use crate::crust::ParticleEmitter;
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+};
+use std::time::Duration;
+
+pub struct Emitter {
+    pub id: String,
+    pub config: ParticleEmitter,
+
+    // Texture of the sprite sheet and bounding box of the frame particles are
+    // rendered with.
+    pub texture_id: String,
+    pub frame: Rect,
+    pub particles: Vec<Particle>,
+
+    active: bool,
+    burst_spawned: bool,
+    elapsed_time: Duration,
+    spawn_debt: f64,
+    rng: Rng,
+}
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: (f64, f64),
+    // Position before the last simulation step, for interpolated rendering.
+    pub previous_position: (f64, f64),
+    pub velocity: (f64, f64),
+    pub age: Duration,
+    pub lifetime: Duration,
+}
+
+impl Emitter {
+    pub fn new(
+        id: String,
+        config: ParticleEmitter,
+        texture_id: String,
+        frame: Rect,
+        seed: u64,
+    ) -> Self {
+        Emitter {
+            id,
+            config,
+            texture_id,
+            frame,
+            particles: vec![],
+            active: true,
+            burst_spawned: false,
+            elapsed_time: Duration::ZERO,
+            spawn_debt: 0.0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Stops spawning new particles. Live particles continue to the end of
+    /// their lifetime.
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    /// True when the emitter is stopped and has no live particles.
+    pub fn finished(&self) -> bool {
+        !self.active && self.particles.is_empty()
+    }
+
+    /// Advances the simulation of live particles and spawns new ones at the
+    /// `origin`, which is the top-left corner of the scene node the emitter is
+    /// attached to.
+    pub fn update(&mut self, time_since_last_frame: Duration, origin: Point) {
+        let dt = time_since_last_frame.as_secs_f64();
+        let gravity = match &self.config.gravity {
+            Some(gravity) => (gravity.x, gravity.y),
+            None => (0.0, 0.0),
+        };
+
+        for particle in &mut self.particles {
+            particle.previous_position = particle.position;
+            particle.age += time_since_last_frame;
+            particle.velocity.0 += gravity.0 * dt;
+            particle.velocity.1 += gravity.1 * dt;
+            particle.position.0 += particle.velocity.0 * dt;
+            particle.position.1 += particle.velocity.1 * dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        if !self.active {
+            return;
+        }
+
+        if !self.burst_spawned {
+            self.burst_spawned = true;
+            for _ in 0..self.config.burst_count {
+                self.spawn(origin);
+            }
+        }
+
+        self.spawn_debt += self.config.spawn_rate * dt;
+        while self.spawn_debt >= 1.0 {
+            self.spawn_debt -= 1.0;
+            self.spawn(origin);
+        }
+
+        self.elapsed_time += time_since_last_frame;
+        if self.config.duration > 0
+            && self.elapsed_time >= Duration::from_millis(self.config.duration as u64)
+        {
+            self.active = false;
+        }
+    }
+
+    fn spawn(&mut self, origin: Point) {
+        if self.config.max_particles > 0
+            && self.particles.len() >= self.config.max_particles as usize
+        {
+            return;
+        }
+
+        let angle = self
+            .rng
+            .spread(self.config.angle, self.config.angle_spread)
+            .to_radians();
+        let speed = self.rng.spread(self.config.speed, self.config.speed_spread);
+        let lifetime = self.rng.spread(
+            self.config.lifetime as f64,
+            self.config.lifetime_spread as f64,
+        );
+        let offset = match &self.config.offset {
+            Some(offset) => (offset.x, offset.y),
+            None => (0.0, 0.0),
+        };
+
+        let position = (origin.x() as f64 + offset.0, origin.y() as f64 + offset.1);
+        self.particles.push(Particle {
+            position,
+            previous_position: position,
+            velocity: (speed * angle.cos(), speed * angle.sin()),
+            age: Duration::ZERO,
+            lifetime: Duration::from_millis(lifetime.max(0.0) as u64),
+        });
+    }
+
+    /// Returns the colour of a particle at its current age.
+    pub fn colour(&self, particle: &Particle) -> Color {
+        let start = self
+            .config
+            .start_colour
+            .as_ref()
+            .map_or(Color::WHITE, Color::from);
+        let end = self.config.end_colour.as_ref().map_or(start, Color::from);
+
+        let t = particle.progress();
+        Color::RGBA(
+            lerp(start.r as f64, end.r as f64, t) as u8,
+            lerp(start.g as f64, end.g as f64, t) as u8,
+            lerp(start.b as f64, end.b as f64, t) as u8,
+            lerp(start.a as f64, end.a as f64, t) as u8,
+        )
+    }
+
+    /// Returns the on-canvas rectangle of a particle at its current age,
+    /// centred on its position.
+    pub fn aabb(&self, particle: &Particle) -> Rect {
+        let scale = match self.config.start_scale == 0.0 && self.config.end_scale == 0.0 {
+            true => 1.0,
+            false => lerp(
+                self.config.start_scale,
+                self.config.end_scale,
+                particle.progress(),
+            ),
+        };
+
+        let mut aabb = Rect::new(
+            0,
+            0,
+            (self.frame.width() as f64 * scale).max(1.0) as u32,
+            (self.frame.height() as f64 * scale).max(1.0) as u32,
+        );
+        aabb.center_on(Point::new(
+            particle.position.0 as i32,
+            particle.position.1 as i32,
+        ));
+        aabb
+    }
+}
+
+impl Particle {
+    /// Distance the particle moved in the last simulation step.
+    pub fn last_step(&self) -> Point {
+        Point::new(
+            (self.position.0 - self.previous_position.0).round() as i32,
+            (self.position.1 - self.previous_position.1).round() as i32,
+        )
+    }
+
+    /// Fraction of the particle's lifetime that has passed in [0, 1].
+    pub fn progress(&self) -> f64 {
+        match self.lifetime.is_zero() {
+            true => 1.0,
+            false => (self.age.as_secs_f64() / self.lifetime.as_secs_f64()).min(1.0),
+        }
+    }
+}
+
+fn lerp(start: f64, end: f64, t: f64) -> f64 {
+    start + (end - start) * t
+}
+
+/// Small xorshift generator used for spreading particle parameters. Particles
+/// only need cheap noise and a fixed seed keeps emitters reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on a zero state.
+        Rng(seed.max(1))
+    }
+
+    /// Returns a value in [0, 1).
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a value in [value - spread / 2, value + spread / 2).
+    fn spread(&mut self, value: f64, spread: f64) -> f64 {
+        value + spread * (self.next() - 0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crust::{RgBa, Vector};
+
+    fn emitter(config: ParticleEmitter) -> Emitter {
+        Emitter::new(
+            "sparks".to_owned(),
+            config,
+            "sparks".to_owned(),
+            Rect::new(0, 0, 4, 4),
+            42,
+        )
+    }
+
+    #[test]
+    fn burst_spawns_once() {
+        let mut emitter = emitter(ParticleEmitter {
+            burst_count: 5,
+            lifetime: 1000,
+            ..Default::default()
+        });
+
+        emitter.update(Duration::from_millis(16), Point::new(10, 20));
+        assert_eq!(emitter.particles.len(), 5);
+        assert_eq!(emitter.particles[0].position, (10.0, 20.0));
+
+        emitter.update(Duration::from_millis(16), Point::new(10, 20));
+        assert_eq!(emitter.particles.len(), 5);
+    }
+
+    #[test]
+    fn spawn_rate_accumulates_over_frames() {
+        let mut emitter = emitter(ParticleEmitter {
+            spawn_rate: 10.0,
+            lifetime: 1000,
+            ..Default::default()
+        });
+
+        emitter.update(Duration::from_millis(50), Point::new(0, 0));
+        assert_eq!(emitter.particles.len(), 0);
+
+        emitter.update(Duration::from_millis(60), Point::new(0, 0));
+        assert_eq!(emitter.particles.len(), 1);
+
+        emitter.update(Duration::from_millis(200), Point::new(0, 0));
+        assert_eq!(emitter.particles.len(), 3);
+    }
+
+    #[test]
+    fn max_particles_caps_spawning() {
+        let mut emitter = emitter(ParticleEmitter {
+            burst_count: 10,
+            max_particles: 4,
+            lifetime: 1000,
+            ..Default::default()
+        });
+
+        emitter.update(Duration::from_millis(16), Point::new(0, 0));
+        assert_eq!(emitter.particles.len(), 4);
+    }
+
+    #[test]
+    fn particles_expire_and_emitter_finishes() {
+        let mut emitter = emitter(ParticleEmitter {
+            burst_count: 3,
+            lifetime: 100,
+            duration: 50,
+            ..Default::default()
+        });
+
+        emitter.update(Duration::from_millis(50), Point::new(0, 0));
+        assert_eq!(emitter.particles.len(), 3);
+        assert_eq!(emitter.finished(), false);
+
+        emitter.update(Duration::from_millis(100), Point::new(0, 0));
+        assert_eq!(emitter.particles.len(), 0);
+        assert_eq!(emitter.finished(), true);
+    }
+
+    #[test]
+    fn gravity_accelerates_particles() {
+        let mut emitter = emitter(ParticleEmitter {
+            burst_count: 1,
+            lifetime: 10000,
+            gravity: Some(Vector {
+                y: 100.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        emitter.update(Duration::ZERO, Point::new(0, 0));
+        emitter.update(Duration::from_secs(1), Point::new(0, 0));
+        assert_eq!(emitter.particles[0].velocity, (0.0, 100.0));
+        assert_eq!(emitter.particles[0].position, (0.0, 100.0));
+    }
+
+    #[test]
+    fn colour_and_scale_over_life() {
+        let emitter = emitter(ParticleEmitter {
+            start_colour: Some(RgBa {
+                red: 255,
+                green: 0,
+                blue: 0,
+                alpha: 255,
+            }),
+            end_colour: Some(RgBa {
+                red: 255,
+                green: 255,
+                blue: 0,
+                alpha: 0,
+            }),
+            start_scale: 1.0,
+            end_scale: 3.0,
+            ..Default::default()
+        });
+        let particle = Particle {
+            position: (10.0, 10.0),
+            previous_position: (10.0, 10.0),
+            velocity: (0.0, 0.0),
+            age: Duration::from_millis(50),
+            lifetime: Duration::from_millis(100),
+        };
+
+        assert_eq!(emitter.colour(&particle), Color::RGBA(255, 127, 0, 127));
+        assert_eq!(emitter.aabb(&particle), Rect::new(6, 6, 8, 8));
+    }
+}
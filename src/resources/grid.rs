@@ -0,0 +1,245 @@
+use super::{Orientation, StaggerAxis, StaggerIndex, TileMap};
+use sdl2::rect::{Point, Rect};
+
+/// Layout of the tiles of a map in the world, which depends on the map's
+/// orientation. Converts between tile coordinates (column, row) and world
+/// coordinates in pixels.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileGrid {
+    orientation: Orientation,
+    tile_width: i32,
+    tile_height: i32,
+
+    // Staggered and hexagonal maps shift every other row, or column if
+    // `stagger_x` is set, by half a tile.
+    stagger_x: bool,
+    stagger_even: bool,
+    side_length: i32,
+
+    // Isometric maps place the top corner of tile (0, 0) at this x, so that
+    // the map starts at x = 0.
+    origin_x: i32,
+}
+
+impl TileGrid {
+    pub fn new(map: &TileMap) -> Self {
+        let mut grid = TileGrid {
+            orientation: map.orientation,
+            tile_width: map.tilewidth as i32,
+            tile_height: map.tileheight as i32,
+            stagger_x: map.staggeraxis == StaggerAxis::X,
+            stagger_even: map.staggerindex == StaggerIndex::Even,
+            side_length: 0,
+            origin_x: 0,
+        };
+        match map.orientation {
+            Orientation::Isometric => grid.origin_x = map.height as i32 * grid.tile_width / 2,
+            Orientation::Hexagonal => grid.side_length = map.hexsidelength as i32,
+            Orientation::Orthogonal | Orientation::Staggered => {}
+        }
+        // Tiled ignores the odd pixel of shifted tiles.
+        if matches!(
+            map.orientation,
+            Orientation::Staggered | Orientation::Hexagonal
+        ) {
+            grid.tile_width &= !1;
+            grid.tile_height &= !1;
+        }
+        grid
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Returns the box of the tile at (`column`, `row`) in world coordinates.
+    /// Tiles overlap the boxes of their neighbours in all but orthogonal maps.
+    pub fn tile_rect(&self, column: i32, row: i32) -> Rect {
+        let (width, height) = (self.tile_width as u32, self.tile_height as u32);
+        match self.orientation {
+            Orientation::Orthogonal => Rect::new(
+                column * self.tile_width,
+                row * self.tile_height,
+                width,
+                height,
+            ),
+            Orientation::Isometric => Rect::new(
+                self.origin_x + (column - row - 1) * self.tile_width / 2,
+                (column + row) * self.tile_height / 2,
+                width,
+                height,
+            ),
+            Orientation::Staggered | Orientation::Hexagonal => {
+                let (column_width, row_height) = self.stagger_steps();
+                match self.stagger_x {
+                    true => Rect::new(
+                        column * column_width,
+                        row * self.tile_height + self.is_shifted(column) as i32 * row_height,
+                        width,
+                        height,
+                    ),
+                    false => Rect::new(
+                        column * self.tile_width + self.is_shifted(row) as i32 * column_width,
+                        row * row_height,
+                        width,
+                        height,
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Returns the (column, row) of the tile at `point` in world coordinates.
+    pub fn tile_at(&self, point: Point) -> (i32, i32) {
+        let (x, y) = (point.x() as f64, point.y() as f64);
+        let (width, height) = (self.tile_width as f64, self.tile_height as f64);
+        match self.orientation {
+            Orientation::Orthogonal => ((x / width).floor() as i32, (y / height).floor() as i32),
+            Orientation::Isometric => {
+                let across = (x - self.origin_x as f64) / (width / 2.0);
+                let down = y / (height / 2.0);
+                (
+                    ((down + across) / 2.0).floor() as i32,
+                    ((down - across) / 2.0).floor() as i32,
+                )
+            }
+            Orientation::Staggered | Orientation::Hexagonal => self.nearest_tile(x, y),
+        }
+    }
+
+    /// Converts a position of an object in a Tiled map to world coordinates.
+    /// Tiled positions objects of isometric maps along the tile axes, in units
+    /// of the tile height. Other maps use world coordinates.
+    pub fn object_to_world(&self, x: i32, y: i32) -> Point {
+        match self.orientation {
+            Orientation::Isometric => {
+                let column = x as f64 / self.tile_height as f64;
+                let row = y as f64 / self.tile_height as f64;
+                Point::new(
+                    self.origin_x + ((column - row) * self.tile_width as f64 / 2.0).round() as i32,
+                    ((column + row) * self.tile_height as f64 / 2.0).round() as i32,
+                )
+            }
+            _ => Point::new(x, y),
+        }
+    }
+
+    /// Returns the extent in world coordinates of the tiles within `tiles`,
+    /// given in tile coordinates.
+    pub fn bounds(&self, tiles: Rect) -> Rect {
+        // The tiles along the border of the area determine its extent.
+        let (left, top) = (tiles.left(), tiles.top());
+        let (right, bottom) = (tiles.right() - 1, tiles.bottom() - 1);
+        (left..=right)
+            .flat_map(|column| [(column, top), (column, bottom)])
+            .chain((top..=bottom).flat_map(|row| [(left, row), (right, row)]))
+            .map(|(column, row)| self.tile_rect(column, row))
+            .reduce(|bounds, tile| bounds.union(tile))
+            .unwrap_or_else(|| Rect::new(0, 0, 0, 0))
+    }
+
+    // Returns the horizontal distance between columns and the vertical one
+    // between rows of staggered and hexagonal maps, for the shifted axis.
+    fn stagger_steps(&self) -> (i32, i32) {
+        let (side_x, side_y) = match self.stagger_x {
+            true => (self.side_length, 0),
+            false => (0, self.side_length),
+        };
+        (
+            (self.tile_width - side_x) / 2 + side_x,
+            (self.tile_height - side_y) / 2 + side_y,
+        )
+    }
+
+    fn is_shifted(&self, index: i32) -> bool {
+        (index.rem_euclid(2) == 1) != self.stagger_even
+    }
+
+    // The tile that contains a point of a staggered or hexagonal map is the
+    // one with the nearest centre. Vertical distances between staggered tiles
+    // are scaled to make them square, which makes the area nearest to their
+    // centre match their diamond shape.
+    fn nearest_tile(&self, x: f64, y: f64) -> (i32, i32) {
+        let (column_width, row_height) = self.stagger_steps();
+        let (column, row) = match self.stagger_x {
+            true => (
+                (x / column_width as f64).floor() as i32,
+                (y / self.tile_height as f64).floor() as i32,
+            ),
+            false => (
+                (x / self.tile_width as f64).floor() as i32,
+                (y / row_height as f64).floor() as i32,
+            ),
+        };
+        let scale = match self.orientation {
+            Orientation::Staggered => self.tile_width as f64 / self.tile_height as f64,
+            _ => 1.0,
+        };
+
+        let distance = |(column, row): (i32, i32)| {
+            let centre = self.tile_rect(column, row).center();
+            let dx = centre.x() as f64 - x;
+            let dy = (centre.y() as f64 - y) * scale;
+            dx * dx + dy * dy
+        };
+        (column - 1..=column + 1)
+            .flat_map(|column| (row - 1..=row + 1).map(move |row| (column, row)))
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+            .unwrap_or((column, row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(orientation: &str, extra: &str) -> TileGrid {
+        let map: TileMap = serde_json::from_str(&format!(
+            r#"{{
+                "width": 4, "height": 4, "tilewidth": 64, "tileheight": 32,
+                "orientation": "{orientation}", "infinite": false,
+                "tilesets": [], "layers": [] {extra}
+            }}"#
+        ))
+        .unwrap();
+        map.grid()
+    }
+
+    #[test]
+    fn isometric_tiles_round_trip() {
+        let grid = grid("isometric", "");
+        assert_eq!(grid.tile_rect(0, 0), Rect::new(96, 0, 64, 32));
+        assert_eq!(grid.tile_rect(0, 3), Rect::new(0, 48, 64, 32));
+        assert_eq!(
+            grid.bounds(Rect::new(0, 0, 4, 4)),
+            Rect::new(0, 0, 256, 128)
+        );
+        for (column, row) in [(0, 0), (3, 1), (2, 3)] {
+            let centre = grid.tile_rect(column, row).center();
+            assert_eq!(grid.tile_at(centre), (column, row));
+        }
+        assert_eq!(grid.object_to_world(32, 32), Point::new(128, 32));
+    }
+
+    #[test]
+    fn staggered_and_hexagonal_tiles_round_trip() {
+        let staggered = grid(
+            "staggered",
+            r#", "staggeraxis": "y", "staggerindex": "odd""#,
+        );
+        assert_eq!(staggered.tile_rect(1, 1), Rect::new(96, 16, 64, 32));
+        let hexagonal = grid(
+            "hexagonal",
+            r#", "staggeraxis": "x", "staggerindex": "even", "hexsidelength": 32"#,
+        );
+        assert_eq!(hexagonal.tile_rect(0, 0), Rect::new(0, 16, 64, 32));
+        assert_eq!(hexagonal.tile_rect(1, 0), Rect::new(48, 0, 64, 32));
+
+        for grid in [staggered, hexagonal] {
+            for (column, row) in [(0, 0), (1, 1), (2, 3), (3, 2)] {
+                let centre = grid.tile_rect(column, row).center();
+                assert_eq!(grid.tile_at(centre), (column, row));
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use sdl2::rect::{Point, Rect};
+
+/// State for rendering in between fixed simulation steps.
+#[derive(Default)]
+pub struct Interpolation {
+    // Fraction of a simulation step that passed since the last step.
+    pub alpha: f64,
+
+    // Viewport before the last simulation step.
+    pub previous_viewport: Option<Rect>,
+}
+
+impl Interpolation {
+    /// Returns the rectangle `current` moved towards its location before the
+    /// last simulation step.
+    pub fn rect(&self, previous: Point, current: Rect) -> Rect {
+        let mut rect = current;
+        rect.reposition(Point::new(
+            lerp(previous.x(), current.x(), self.alpha),
+            lerp(previous.y(), current.y(), self.alpha),
+        ));
+        rect
+    }
+
+    pub fn viewport(&self, current: Rect) -> Rect {
+        match self.previous_viewport {
+            Some(previous) => self.rect(previous.top_left(), current),
+            None => current,
+        }
+    }
+}
+
+fn lerp(previous: i32, current: i32, alpha: f64) -> i32 {
+    (previous as f64 + (current - previous) as f64 * alpha).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_between_steps() {
+        let interpolation = Interpolation {
+            alpha: 0.25,
+            previous_viewport: Some(Rect::new(0, 100, 320, 240)),
+        };
+
+        assert_eq!(
+            interpolation.rect(Point::new(10, 10), Rect::new(18, 10, 16, 16)),
+            Rect::new(12, 10, 16, 16)
+        );
+        assert_eq!(
+            interpolation.viewport(Rect::new(40, 100, 320, 240)),
+            Rect::new(10, 100, 320, 240)
+        );
+    }
+}
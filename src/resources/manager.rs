@@ -1,18 +1,54 @@
+use super::AssetSource;
 use crate::core::Status;
+use crate::crust::CacheStats;
 use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
 
 // A generic resource manager that handles loading external resources and
 // maintaining an in-memory cache for quick retrieval.
+//
+// Resources stay cached until they are unloaded or, if the manager has a
+// memory budget, evicted in least recently used order. Resources that are
+// referenced through a ResourceRef are never unloaded. Inserted resources,
+// e.g. preloaded ones, are not evicted before they are first used.
 pub struct ResourceManager<Key, Resource, Loader>
 where
     Key: Hash + Eq,
     Loader: ResourceLoader<Resource>,
 {
-    resource_path: String,
+    source: Arc<dyn AssetSource>,
     loader: Loader,
-    cache: HashMap<Key, Resource>,
+    cache: HashMap<Key, CacheEntry<Resource>>,
+
+    // Maximum total size of cached resources in bytes, if any.
+    budget: Option<usize>,
+    // Monotonic counter that orders accesses for LRU eviction.
+    accesses: AtomicU64,
+}
+
+struct CacheEntry<Resource> {
+    resource: Resource,
+    refs: ResourceRef,
+    size: usize,
+    last_used: AtomicU64,
+    // Set for inserted resources until they are first used.
+    unused: AtomicBool,
+}
+
+/// Keeps a cached resource from being unloaded or evicted for as long as it
+/// is held.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceRef(Arc<()>);
+
+impl ResourceRef {
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
 }
 
 impl<Key, Resource, Loader> ResourceManager<Key, Resource, Loader>
@@ -20,39 +56,182 @@ where
     Key: Hash + Eq,
     Loader: ResourceLoader<Resource>,
 {
-    pub fn new(resource_path: &str, loader: Loader) -> Self {
+    pub fn new(source: Arc<dyn AssetSource>, loader: Loader) -> Self {
         ResourceManager {
-            resource_path: resource_path.to_owned(),
+            source,
             loader,
             cache: HashMap::new(),
+            budget: None,
+            accesses: AtomicU64::new(0),
         }
     }
 
+    /// Sets the memory budget in bytes. A zero budget means unlimited.
+    pub fn set_budget(&mut self, budget: u64) {
+        self.budget = match budget {
+            0 => None,
+            budget => Some(budget as usize),
+        };
+    }
+
     // Generics magic to allow a HashMap to use String as a key
     // while allowing it to use &str for gets
     pub fn load<'a, D>(&'a mut self, details: &D) -> Result<&'a Resource, Status>
     where
         Loader: ResourceLoader<Resource, Args = D>,
         D: Eq + Hash + ?Sized,
-        Key: Borrow<D> + for<'d> From<&'d D>,
+        Key: Borrow<D> + Clone + for<'d> From<&'d D>,
     {
         if let None = self.cache.get(details) {
-            let resource = self.loader.load(&self.resource_path, details)?;
-            self.cache.insert(details.into(), resource);
+            let resource = self.loader.load(self.source.as_ref(), details)?;
+            let entry = CacheEntry {
+                size: self.loader.size(&resource),
+                resource,
+                refs: ResourceRef::default(),
+                last_used: AtomicU64::new(0),
+                unused: AtomicBool::new(false),
+            };
+            self.cache.insert(details.into(), entry);
+            self.evict(details);
         }
 
         match self.cache.get(details) {
-            Some(resource) => Ok(resource),
+            Some(entry) => {
+                self.touch(entry);
+                Ok(&entry.resource)
+            }
             None => Err(Status::not_found("Resource '{details}' not found")),
         }
     }
 
+    /// Caches a resource that was loaded elsewhere, e.g. preloaded in the
+    /// background. An already cached resource is kept. The resource is not
+    /// evicted until it is first loaded or retrieved.
+    pub fn insert<D>(&mut self, details: &D, resource: Resource)
+    where
+        D: Eq + Hash + ?Sized,
+        Key: Borrow<D> + Clone + for<'d> From<&'d D>,
+    {
+        if self.cache.contains_key(details) {
+            return;
+        }
+
+        let entry = CacheEntry {
+            size: self.loader.size(&resource),
+            resource,
+            refs: ResourceRef::default(),
+            last_used: AtomicU64::new(0),
+            unused: AtomicBool::new(false),
+        };
+        self.touch(&entry);
+        entry.unused.store(true, Ordering::Relaxed);
+        self.cache.insert(details.into(), entry);
+        self.evict(details);
+    }
+
     pub fn get<'a, D>(&'a self, details: &D) -> Option<&'a Resource>
     where
         D: Eq + Hash + ?Sized,
         Key: Borrow<D> + for<'d> From<&'d D>,
     {
-        self.cache.get(details)
+        self.cache.get(details).map(|entry| {
+            self.touch(entry);
+            &entry.resource
+        })
+    }
+
+    /// Returns a reference that keeps the cached resource loaded while held.
+    pub fn acquire<D>(&self, details: &D) -> Option<ResourceRef>
+    where
+        D: Eq + Hash + ?Sized,
+        Key: Borrow<D> + for<'d> From<&'d D>,
+    {
+        self.cache.get(details).map(|entry| entry.refs.clone())
+    }
+
+    /// Removes a resource from the cache. Returns false if the resource was
+    /// not loaded. Fails if the resource is still referenced.
+    pub fn unload<D>(&mut self, details: &D) -> Result<bool, Status>
+    where
+        D: Eq + Hash + std::fmt::Debug + ?Sized,
+        Key: Borrow<D> + for<'d> From<&'d D>,
+    {
+        match self.cache.get(details) {
+            Some(entry) if entry.refs.in_use() => Err(Status::invalid_argument(format!(
+                "Resource {details:?} is still in use"
+            ))),
+            Some(_) => {
+                self.cache.remove(details);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Reloads a resource that is already cached, replacing it in place.
+    /// Returns false if the resource was never loaded. On failure the cached
+    /// resource is kept.
+    pub fn reload<D>(&mut self, details: &D) -> Result<bool, Status>
+    where
+        Loader: ResourceLoader<Resource, Args = D>,
+        D: Eq + Hash + ?Sized,
+        Key: Borrow<D> + for<'d> From<&'d D>,
+    {
+        match self.cache.get_mut(details) {
+            Some(entry) => {
+                entry.resource = self.loader.load(self.source.as_ref(), details)?;
+                entry.size = self.loader.size(&entry.resource);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            resources: self.cache.len() as u32,
+            bytes: self.cache.values().map(|entry| entry.size as u64).sum(),
+            budget: self.budget.unwrap_or_default() as u64,
+        }
+    }
+
+    fn touch(&self, entry: &CacheEntry<Resource>) {
+        let access = self.accesses.fetch_add(1, Ordering::Relaxed) + 1;
+        entry.last_used.store(access, Ordering::Relaxed);
+        entry.unused.store(false, Ordering::Relaxed);
+    }
+
+    // Evicts least recently used resources that are not referenced until the
+    // cache fits its budget. The resource that was just loaded is kept, as
+    // well as inserted resources that were not used yet.
+    fn evict<D>(&mut self, loaded: &D)
+    where
+        D: Eq + Hash + ?Sized,
+        Key: Borrow<D> + Clone,
+    {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return,
+        };
+
+        let mut total: usize = self.cache.values().map(|entry| entry.size).sum();
+        while total > budget {
+            let lru = self
+                .cache
+                .iter()
+                .filter(|(key, entry)| {
+                    (*key).borrow() != loaded
+                        && !entry.refs.in_use()
+                        && !entry.unused.load(Ordering::Relaxed)
+                })
+                .min_by_key(|(_, entry)| entry.last_used.load(Ordering::Relaxed))
+                .map(|(key, _)| key.clone());
+
+            match lru.and_then(|key| self.cache.remove::<Key>(&key)) {
+                Some(entry) => total -= entry.size,
+                None => break,
+            }
+        }
     }
 }
 
@@ -63,7 +242,19 @@ where
     Loader: ResourceLoader<Resource>,
 {
     pub fn set_resources(&mut self, resources: Vec<(Key, Resource)>) {
-        self.cache = resources.into_iter().collect();
+        self.cache = resources
+            .into_iter()
+            .map(|(key, resource)| {
+                let entry = CacheEntry {
+                    size: self.loader.size(&resource),
+                    resource,
+                    refs: ResourceRef::default(),
+                    last_used: AtomicU64::new(0),
+                    unused: AtomicBool::new(false),
+                };
+                (key, entry)
+            })
+            .collect();
     }
 }
 
@@ -71,5 +262,83 @@ where
 pub trait ResourceLoader<Resource> {
     type Args: ?Sized;
 
-    fn load(&self, path: &str, resource: &Self::Args) -> Result<Resource, Status>;
+    fn load(&self, source: &dyn AssetSource, resource: &Self::Args) -> Result<Resource, Status>;
+
+    /// Approximate memory footprint of a loaded resource in bytes, used for
+    /// enforcing memory budgets.
+    fn size(&self, _resource: &Resource) -> usize {
+        std::mem::size_of::<Resource>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::DirectorySource;
+
+    struct TextLoader;
+
+    impl ResourceLoader<String> for TextLoader {
+        type Args = str;
+
+        fn load(&self, _source: &dyn AssetSource, resource: &str) -> Result<String, Status> {
+            Ok(resource.to_owned())
+        }
+
+        fn size(&self, text: &String) -> usize {
+            text.len()
+        }
+    }
+
+    type TextManager = ResourceManager<String, String, TextLoader>;
+
+    fn text_manager() -> TextManager {
+        TextManager::new(Arc::new(DirectorySource::new("")), TextLoader {})
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut manager = text_manager();
+        manager.set_budget(8);
+
+        manager.load("aaa").unwrap();
+        manager.load("bbb").unwrap();
+        manager.load("aaa").unwrap();
+        manager.load("ccc").unwrap();
+
+        assert!(manager.get("aaa").is_some());
+        assert!(manager.get("bbb").is_none());
+        assert!(manager.get("ccc").is_some());
+        assert_eq!(manager.stats().bytes, 6);
+    }
+
+    #[test]
+    fn referenced_resources_are_kept() {
+        let mut manager = text_manager();
+        manager.set_budget(4);
+
+        manager.load("aaa").unwrap();
+        let aaa = manager.acquire("aaa");
+        manager.load("bbb").unwrap();
+
+        assert!(manager.get("aaa").is_some());
+        assert!(manager.unload("aaa").is_err());
+
+        drop(aaa);
+        assert_eq!(manager.unload("aaa").unwrap(), true);
+        assert_eq!(manager.unload("aaa").unwrap(), false);
+    }
+
+    #[test]
+    fn inserted_resources_are_kept_until_used() {
+        let mut manager = text_manager();
+        manager.set_budget(4);
+
+        manager.insert("aaa", "aaa".to_owned());
+        manager.load("bbb").unwrap();
+        assert!(manager.get("aaa").is_some());
+
+        manager.load("ccc").unwrap();
+        assert!(manager.get("aaa").is_none());
+    }
 }
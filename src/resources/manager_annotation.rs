@@ -1,22 +1,45 @@
+use super::AssetSource;
 use crate::core::Status;
+use crate::crust::CacheStats;
 use std::borrow::Borrow;
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::rc::Rc;
+use std::sync::Arc;
 
 // A generic resource manager that handles loading external resources and
 // maintaining an in-memory cache for quick retrieval.
 //
 // Simlilar to [resources::ResourceManager] but specific for Resources whose
 // lifetime is tied to that of their associated Loader.
+//
+// Resources are used for rendering, so instead of references they are kept in
+// memory while they are used in the current frame. Resources that were not
+// used recently are evicted in least recently used order at the end of a
+// frame if the cache exceeds its memory budget. Inserted resources, e.g.
+// preloaded ones, are not evicted before they are first used.
 pub struct ResourceManagerWithAnnotation<'l, Key, Resource, Loader>
 where
     Key: Hash + Eq,
     Loader: 'l + ResourceLoaderWithAnnotation<'l, Resource>,
 {
-    resource_path: String,
+    source: Arc<dyn AssetSource>,
     loader: &'l Loader,
-    cache: HashMap<Key, Rc<Resource>>,
+    cache: HashMap<Key, CacheEntry<Resource>>,
+
+    // Maximum total size of cached resources in bytes, if any.
+    budget: Option<usize>,
+    frame: u64,
+}
+
+struct CacheEntry<Resource> {
+    resource: Rc<Resource>,
+    size: usize,
+    // Last frame that the resource was used in.
+    last_used: Cell<u64>,
+    // Set for inserted resources until they are first used.
+    unused: Cell<bool>,
 }
 
 impl<'l, Key, Resource, Loader> ResourceManagerWithAnnotation<'l, Key, Resource, Loader>
@@ -24,14 +47,24 @@ where
     Key: Hash + Eq,
     Loader: ResourceLoaderWithAnnotation<'l, Resource>,
 {
-    pub fn new(resource_path: &str, loader: &'l Loader) -> Self {
+    pub fn new(source: Arc<dyn AssetSource>, loader: &'l Loader) -> Self {
         ResourceManagerWithAnnotation {
-            resource_path: resource_path.to_owned(),
+            source,
             loader,
             cache: HashMap::new(),
+            budget: None,
+            frame: 0,
         }
     }
 
+    /// Sets the memory budget in bytes. A zero budget means unlimited.
+    pub fn set_budget(&mut self, budget: u64) {
+        self.budget = match budget {
+            0 => None,
+            budget => Some(budget as usize),
+        };
+    }
+
     // Generics magic to allow a HashMap to use String as a key
     // while allowing it to use &str for gets
     pub fn load<D>(&mut self, details: &D) -> Result<Rc<Resource>, Status>
@@ -40,24 +73,133 @@ where
         D: Eq + Hash + ?Sized,
         Key: Borrow<D> + for<'a> From<&'a D>,
     {
-        self.cache.get(details).cloned().map_or_else(
-            || {
-                let resource = Rc::new(self.loader.load(&self.resource_path, details)?);
-                self.cache.insert(details.into(), resource.clone());
-                Ok(resource)
+        if let Some(entry) = self.cache.get(details) {
+            entry.last_used.set(self.frame);
+            entry.unused.set(false);
+            return Ok(Rc::clone(&entry.resource));
+        }
+
+        let resource = Rc::new(self.loader.load(self.source.as_ref(), details)?);
+        self.cache.insert(
+            details.into(),
+            CacheEntry {
+                size: self.loader.size(&resource),
+                resource: Rc::clone(&resource),
+                last_used: Cell::new(self.frame),
+                unused: Cell::new(false),
             },
-            Ok,
-        )
+        );
+        Ok(resource)
+    }
+
+    /// Caches a resource that was loaded elsewhere, e.g. preloaded in the
+    /// background. An already cached resource is kept. The resource is not
+    /// evicted until it is first loaded or retrieved.
+    pub fn insert<D>(&mut self, details: &D, resource: Resource)
+    where
+        D: Eq + Hash + ?Sized,
+        Key: Borrow<D> + for<'a> From<&'a D>,
+    {
+        if self.cache.contains_key(details) {
+            return;
+        }
+
+        self.cache.insert(
+            details.into(),
+            CacheEntry {
+                size: self.loader.size(&resource),
+                resource: Rc::new(resource),
+                last_used: Cell::new(self.frame),
+                unused: Cell::new(true),
+            },
+        );
     }
 
     pub fn get<D>(&self, details: &D) -> Option<Rc<Resource>>
     where
         D: Eq + Hash + ?Sized,
         Key: Borrow<D> + for<'d> From<&'d D>,
+    {
+        self.cache.get(details).map(|entry| {
+            entry.last_used.set(self.frame);
+            entry.unused.set(false);
+            Rc::clone(&entry.resource)
+        })
+    }
+
+    /// Removes a resource from the cache. Returns false if the resource was
+    /// not loaded. Fails if the resource is still held elsewhere.
+    pub fn unload<D>(&mut self, details: &D) -> Result<bool, Status>
+    where
+        D: Eq + Hash + std::fmt::Debug + ?Sized,
+        Key: Borrow<D> + for<'d> From<&'d D>,
     {
         match self.cache.get(details) {
-            Some(resource) => Some(Rc::clone(resource)),
-            None => None,
+            Some(entry) if Rc::strong_count(&entry.resource) > 1 => Err(Status::invalid_argument(
+                format!("Resource {details:?} is still in use"),
+            )),
+            Some(_) => {
+                self.cache.remove(details);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Reloads a resource that is already cached. Holders of the previous
+    /// resource keep it alive until they release it. Returns false if the
+    /// resource was never loaded.
+    pub fn reload<D>(&mut self, details: &D) -> Result<bool, Status>
+    where
+        Loader: ResourceLoaderWithAnnotation<'l, Resource, Args = D>,
+        D: Eq + Hash + ?Sized,
+        Key: Borrow<D> + for<'a> From<&'a D>,
+    {
+        match self.cache.get_mut(details) {
+            Some(entry) => {
+                entry.resource = Rc::new(self.loader.load(self.source.as_ref(), details)?);
+                entry.size = self.loader.size(&entry.resource);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Evicts resources that were not used in the current frame, least
+    /// recently used first, until the cache fits its budget. Advances to the
+    /// next frame.
+    pub fn end_frame(&mut self)
+    where
+        Key: Clone,
+    {
+        if let Some(budget) = self.budget {
+            let mut total: usize = self.cache.values().map(|entry| entry.size).sum();
+            while total > budget {
+                let lru = self
+                    .cache
+                    .iter()
+                    .filter(|(_, entry)| {
+                        entry.last_used.get() < self.frame
+                            && Rc::strong_count(&entry.resource) == 1
+                            && !entry.unused.get()
+                    })
+                    .min_by_key(|(_, entry)| entry.last_used.get())
+                    .map(|(key, _)| key.clone());
+
+                match lru.and_then(|key| self.cache.remove(&key)) {
+                    Some(entry) => total -= entry.size,
+                    None => break,
+                }
+            }
+        }
+        self.frame += 1;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            resources: self.cache.len() as u32,
+            bytes: self.cache.values().map(|entry| entry.size as u64).sum(),
+            budget: self.budget.unwrap_or_default() as u64,
         }
     }
 }
@@ -66,5 +208,11 @@ where
 pub trait ResourceLoaderWithAnnotation<'l, Resource> {
     type Args: ?Sized;
 
-    fn load(&'l self, path: &str, resource: &Self::Args) -> Result<Resource, Status>;
+    fn load(&'l self, source: &dyn AssetSource, resource: &Self::Args) -> Result<Resource, Status>;
+
+    /// Approximate memory footprint of a loaded resource in bytes, used for
+    /// enforcing memory budgets.
+    fn size(&self, _resource: &Resource) -> usize {
+        std::mem::size_of::<Resource>()
+    }
 }
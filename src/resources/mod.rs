@@ -1,13 +1,37 @@
+mod grid;
+mod interpolation;
 mod manager;
 mod manager_annotation;
+mod pack;
+mod preload;
+mod screenshot;
+mod source;
 mod sprites;
+#[cfg(test)]
+pub mod testing;
 mod texture;
 mod tiles;
+mod time;
+mod tmx;
+mod validation;
 mod viewport;
+mod window;
 
-pub use manager::{ResourceLoader, ResourceManager};
+pub use grid::TileGrid;
+pub use interpolation::Interpolation;
+pub use manager::{ResourceLoader, ResourceManager, ResourceRef};
 pub use manager_annotation::{ResourceLoaderWithAnnotation, ResourceManagerWithAnnotation};
-pub use sprites::{Frame, Sprite, SpriteManager};
-pub use texture::TextureManager;
+pub use pack::{PackSource, PackWriter};
+pub use preload::{DecodedImage, PendingPreloads, Preload, Preloaded};
+pub use screenshot::PendingScreenshots;
+pub use source::{resolve_path, AssetSource, DirectorySource, LayeredSource};
+pub use sprites::{Frame, Sprite, SpriteLoader, SpriteManager, TileData, TileFrame};
+pub use texture::{
+    set_texture_alpha, set_texture_colour, PendingTextureUnloads, TextureManager, TextureStats,
+};
 pub use tiles::*;
+pub use time::{TimeScale, UnscaledTime};
+pub use tmx::{read_tmx, read_tsx};
+pub use validation::{validate_sprite, validate_tile_map};
 pub use viewport::*;
+pub use window::{PendingWindowChanges, WindowChange};
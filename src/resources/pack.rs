@@ -0,0 +1,270 @@
+use super::AssetSource;
+use crate::core::Status;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    sync::Mutex,
+};
+
+// Pack archive layout, all integers little endian:
+//
+//   magic "CRPK" | version: u32 | entry count: u32
+//   per entry: name length: u32 | name (utf-8) | offset: u64 |
+//              stored size: u64 | size: u64 | compression: u8
+//   entry data
+//
+// Offsets are relative to the start of the archive.
+const MAGIC: &[u8; 4] = b"CRPK";
+const VERSION: u32 = 1;
+
+const STORED: u8 = 0;
+const DEFLATE: u8 = 1;
+
+// Deflate compresses data by a factor of 1032 at most, which bounds the size
+// of the buffer that is reserved for decompressing an entry.
+const MAX_DEFLATE_RATIO: u64 = 1032;
+
+#[derive(Debug)]
+struct PackEntry {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compression: u8,
+}
+
+/// Assets packed in a single archive file built with `crust-pack`.
+pub struct PackSource {
+    file: Mutex<File>,
+    index: HashMap<String, PackEntry>,
+}
+
+impl PackSource {
+    pub fn open(filename: &str) -> Result<Self, Status> {
+        let mut file = File::open(filename)
+            .map_err(|e| Status::new(&format!("Failed to open pack '{filename}'"), e))?;
+        let index = read_index(&mut file)
+            .map_err(|e| Status::new(&format!("Invalid pack '{filename}'"), e))?;
+
+        Ok(PackSource {
+            file: Mutex::new(file),
+            index,
+        })
+    }
+}
+
+impl AssetSource for PackSource {
+    fn read(&self, path: &str) -> Result<Vec<u8>, Status> {
+        let entry = self
+            .index
+            .get(path)
+            .ok_or_else(|| Status::not_found(format!("Asset '{path}' not found in pack")))?;
+
+        let mut stored = vec![0; entry.stored_size as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut stored)?;
+        }
+
+        match entry.compression {
+            STORED => Ok(stored),
+            DEFLATE => {
+                let mut data = Vec::with_capacity(entry.size as usize);
+                DeflateDecoder::new(&stored[..]).read_to_end(&mut data)?;
+                Ok(data)
+            }
+            compression => Err(Status::internal(format!(
+                "Asset '{path}' has unknown compression {compression}"
+            ))),
+        }
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.index.contains_key(path)
+    }
+}
+
+// Sizes in the index are checked against the length of the archive before
+// any buffers are allocated for them, so that corrupt archives fail to open.
+fn read_index(file: &mut File) -> std::io::Result<HashMap<String, PackEntry>> {
+    let len = file.metadata()?.len();
+    let mut magic = [0; 4];
+    file.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a pack archive".to_owned()));
+    }
+    let version = read_u32(file)?;
+    if version != VERSION {
+        return Err(invalid_data(format!("unsupported version {version}")));
+    }
+
+    let count = read_u32(file)?;
+    let mut index = HashMap::new();
+    for _ in 0..count {
+        let name_len = read_u32(file)? as u64;
+        if name_len > len.saturating_sub(file.stream_position()?) {
+            return Err(invalid_data("entry name exceeds the archive".to_owned()));
+        }
+        let mut name = vec![0; name_len as usize];
+        file.read_exact(&mut name)?;
+        let name = String::from_utf8(name).map_err(|e| invalid_data(e.to_string()))?;
+
+        let entry = PackEntry {
+            offset: read_u64(file)?,
+            stored_size: read_u64(file)?,
+            size: read_u64(file)?,
+            compression: {
+                let mut compression = [0; 1];
+                file.read_exact(&mut compression)?;
+                compression[0]
+            },
+        };
+        if entry
+            .offset
+            .checked_add(entry.stored_size)
+            .is_none_or(|end| end > len)
+        {
+            return Err(invalid_data(format!("entry '{name}' exceeds the archive")));
+        }
+        let max_size = match entry.compression {
+            STORED => entry.stored_size,
+            _ => entry.stored_size.saturating_mul(MAX_DEFLATE_RATIO),
+        };
+        if entry.size > max_size {
+            return Err(invalid_data(format!("entry '{name}' has invalid size")));
+        }
+        index.insert(name, entry);
+    }
+    Ok(index)
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn invalid_data(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Builds pack archives that are read by PackSource.
+#[derive(Default)]
+pub struct PackWriter {
+    entries: Vec<(String, Vec<u8>, u64, u8)>,
+}
+
+impl PackWriter {
+    pub fn new() -> Self {
+        PackWriter::default()
+    }
+
+    /// Adds an asset under `path`. Compressed assets are stored uncompressed
+    /// if compression does not make them smaller.
+    pub fn add(&mut self, path: &str, data: Vec<u8>, compress: bool) -> Result<(), Status> {
+        let size = data.len() as u64;
+        let (stored, compression) = match compress {
+            true => {
+                let mut encoder = DeflateEncoder::new(vec![], Compression::best());
+                encoder.write_all(&data)?;
+                let compressed = encoder.finish()?;
+                match compressed.len() < data.len() {
+                    true => (compressed, DEFLATE),
+                    false => (data, STORED),
+                }
+            }
+            false => (data, STORED),
+        };
+        self.entries
+            .push((path.to_owned(), stored, size, compression));
+        Ok(())
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), Status> {
+        let index_size: usize = self
+            .entries
+            .iter()
+            .map(|(name, ..)| 4 + name.len() + 8 * 3 + 1)
+            .sum();
+        let mut offset = (MAGIC.len() + 4 + 4 + index_size) as u64;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+        for (name, stored, size, compression) in &self.entries {
+            writer.write_all(&(name.len() as u32).to_le_bytes())?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&offset.to_le_bytes())?;
+            writer.write_all(&(stored.len() as u64).to_le_bytes())?;
+            writer.write_all(&size.to_le_bytes())?;
+            writer.write_all(&[*compression])?;
+            offset += stored.len() as u64;
+        }
+        for (_, stored, ..) in &self.entries {
+            writer.write_all(stored)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_assets_round_trip() {
+        let filename = std::env::temp_dir().join(format!("crust_pack_{}.pak", std::process::id()));
+
+        let text = "tile ".repeat(100).into_bytes();
+        let mut writer = PackWriter::new();
+        writer
+            .add("sprites/hero.json", b"{}".to_vec(), false)
+            .unwrap();
+        writer.add("maps/level1.json", text.clone(), true).unwrap();
+        writer.write(&mut File::create(&filename).unwrap()).unwrap();
+
+        let source = PackSource::open(filename.to_str().unwrap()).unwrap();
+        assert_eq!(source.read("sprites/hero.json").unwrap(), b"{}");
+        assert_eq!(source.read("maps/level1.json").unwrap(), text);
+        assert_eq!(source.index["maps/level1.json"].compression, DEFLATE);
+        assert!(!source.contains("maps/level2.json"));
+
+        std::fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn corrupt_sizes_are_rejected() {
+        let filename =
+            std::env::temp_dir().join(format!("crust_corrupt_sizes_{}.pak", std::process::id()));
+        let open = |pack: &[u8]| {
+            std::fs::write(&filename, pack).unwrap();
+            PackSource::open(filename.to_str().unwrap())
+        };
+
+        let mut header = MAGIC.to_vec();
+        header.extend(VERSION.to_le_bytes());
+        header.extend(1u32.to_le_bytes());
+        let mut huge_name = header.clone();
+        huge_name.extend(u32::MAX.to_le_bytes());
+        assert!(open(&huge_name).is_err());
+
+        let mut writer = PackWriter::new();
+        writer
+            .add("maps/level1.json", b"{}".to_vec(), false)
+            .unwrap();
+        let mut pack = vec![];
+        writer.write(&mut pack).unwrap();
+        assert!(open(&pack).is_ok());
+        assert!(open(&pack[..pack.len() - 1]).is_err());
+
+        std::fs::remove_file(&filename).unwrap();
+    }
+}
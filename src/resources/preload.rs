@@ -0,0 +1,232 @@
+use super::{AssetSource, ResourceLoader, Sprite, SpriteLoader, TileMap, TileMapLoader};
+use crate::{core::Status, crust::PreloadAction};
+use sdl2::{image::ImageRWops, pixels::PixelFormatEnum, rwops::RWops, surface::Surface};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+/// Preloads requested during the frame. They are started by the Core, which
+/// owns the resource managers that receive the preloaded resources.
+#[derive(Default)]
+pub struct PendingPreloads(pub Vec<PreloadAction>);
+
+/// A resource that was loaded in the background.
+pub enum Preloaded {
+    Sprite(String, Sprite),
+    // Tile maps come together with their tilesets.
+    TileMap(String, TileMap, Vec<(String, Sprite)>),
+    // Textures are decoded in the background but uploaded to the GPU on the
+    // main thread.
+    Texture(String, DecodedImage),
+    Failed(String, Status),
+}
+
+/// A decoded image that is ready to be turned into a texture. It owns its
+/// pixels, so that it can be moved to the main thread.
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    // Bytes per row of pixels.
+    pub pitch: u32,
+    pub format: PixelFormatEnum,
+    pub pixels: Vec<u8>,
+}
+
+impl DecodedImage {
+    /// Returns a surface that uses the image's pixels.
+    pub fn surface(&mut self) -> Result<Surface<'_>, Status> {
+        Ok(Surface::from_data(
+            &mut self.pixels,
+            self.width,
+            self.height,
+            self.pitch,
+            self.format,
+        )?)
+    }
+}
+
+/// A preload that is running on a background thread.
+pub struct Preload {
+    pub event_id: String,
+    pub total: u32,
+    pub processed: u32,
+    pub failed: Vec<String>,
+
+    rx: Receiver<Preloaded>,
+}
+
+impl Preload {
+    pub fn start(source: Arc<dyn AssetSource>, preload_action: PreloadAction) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let total = (preload_action.sprites.len()
+            + preload_action.tile_maps.len()
+            + preload_action.textures.len()) as u32;
+
+        let PreloadAction {
+            event_id,
+            sprites,
+            tile_maps,
+            textures,
+        } = preload_action;
+
+        thread::spawn(move || {
+            let sprite_loader = SpriteLoader {};
+            for sprite in sprites {
+                let preloaded = match sprite_loader.load(source.as_ref(), &sprite) {
+                    Ok(resource) => Preloaded::Sprite(sprite, resource),
+                    Err(e) => Preloaded::Failed(sprite, e),
+                };
+                if tx.send(preloaded).is_err() {
+                    return;
+                }
+            }
+            for tile_map in tile_maps {
+                let preloaded = match load_tile_map(source.as_ref(), &tile_map) {
+                    Ok((map, tilesets)) => Preloaded::TileMap(tile_map, map, tilesets),
+                    Err(e) => Preloaded::Failed(tile_map, e),
+                };
+                if tx.send(preloaded).is_err() {
+                    return;
+                }
+            }
+            for texture in textures {
+                let preloaded = match decode_image(source.as_ref(), &texture) {
+                    Ok(image) => Preloaded::Texture(texture, image),
+                    Err(e) => Preloaded::Failed(texture, e),
+                };
+                if tx.send(preloaded).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Preload {
+            event_id,
+            total,
+            processed: 0,
+            failed: vec![],
+            rx,
+        }
+    }
+
+    /// Returns the next resource that finished loading, if any.
+    pub fn next_preloaded(&mut self) -> Option<Preloaded> {
+        match self.rx.try_recv() {
+            Ok(preloaded) => {
+                self.processed += 1;
+                if let Preloaded::Failed(resource, _) = &preloaded {
+                    self.failed.push(resource.clone());
+                }
+                Some(preloaded)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                // The thread only stops early if it panicked.
+                self.processed = self.total;
+                None
+            }
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.processed >= self.total
+    }
+}
+
+fn load_tile_map(
+    source: &dyn AssetSource,
+    resource: &str,
+) -> Result<(TileMap, Vec<(String, Sprite)>), Status> {
+    let map = TileMapLoader {}.load(source, resource)?;
+    let sprite_loader = SpriteLoader {};
+    let mut tilesets = vec![];
+    for set in &map.tilesets {
+        let tileset = set.resource()?;
+        tilesets.push((tileset.to_owned(), sprite_loader.load(source, tileset)?));
+    }
+    Ok((map, tilesets))
+}
+
+// Images are converted to a format without a palette, so that their pixels
+// can be used on their own.
+fn decode_image(source: &dyn AssetSource, resource: &str) -> Result<DecodedImage, Status> {
+    let filename = format!("{resource}.png");
+    let png = source.read(&filename)?;
+    let surface = RWops::from_bytes(&png)?
+        .load()
+        .and_then(|surface| surface.convert_format(PixelFormatEnum::RGBA32))
+        .map_err(|e| Status::internal(format!("Failed to decode '{filename}': {e}")))?;
+    Ok(DecodedImage {
+        width: surface.width(),
+        height: surface.height(),
+        pitch: surface.pitch(),
+        format: surface.pixel_format_enum(),
+        pixels: surface.with_lock(|pixels| pixels.to_vec()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::testing::MemorySource;
+
+    fn finish(preload: &mut Preload) -> Vec<Preloaded> {
+        let mut preloaded = vec![];
+        while !preload.done() {
+            match preload.next_preloaded() {
+                Some(resource) => preloaded.push(resource),
+                None => thread::yield_now(),
+            }
+        }
+        preloaded
+    }
+
+    #[test]
+    fn resources_load_in_the_background() {
+        let source = MemorySource::default()
+            .with("hero.json", r#"{"texture_id": "hero", "frames": []}"#)
+            .with("terrain.json", r#"{"texture_id": "terrain", "frames": []}"#)
+            .with(
+                "level.json",
+                r#"{
+                    "width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+                    "orientation": "orthogonal", "infinite": false,
+                    "tilesets": [{"firstgid": 1, "source": "terrain.tsx"}],
+                    "layers": []
+                }"#,
+            );
+        let mut preload = Preload::start(
+            Arc::new(source),
+            PreloadAction {
+                event_id: "loading".to_owned(),
+                sprites: vec!["hero".to_owned()],
+                tile_maps: vec!["level".to_owned()],
+                textures: vec!["missing".to_owned()],
+            },
+        );
+        assert_eq!(preload.total, 3);
+
+        let preloaded = finish(&mut preload);
+        assert_eq!(preloaded.len(), 3);
+        assert!(matches!(
+            &preloaded[0],
+            Preloaded::Sprite(resource, sprite)
+                if resource == "hero" && sprite.texture_id == "hero"
+        ));
+        assert!(matches!(
+            &preloaded[1],
+            Preloaded::TileMap(resource, _, tilesets)
+                if resource == "level" && tilesets.len() == 1 && tilesets[0].0 == "terrain"
+        ));
+        assert!(matches!(
+            &preloaded[2],
+            Preloaded::Failed(resource, Status::NotFound(_)) if resource == "missing"
+        ));
+        assert_eq!(preload.processed, 3);
+        assert_eq!(preload.failed, vec!["missing".to_owned()]);
+    }
+}
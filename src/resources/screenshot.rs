@@ -0,0 +1,4 @@
+/// Screenshots requested during the frame. They are captured by the renderer
+/// after the frame is drawn and before it is presented.
+#[derive(Default)]
+pub struct PendingScreenshots(pub Vec<String>);
@@ -0,0 +1,111 @@
+use crate::core::Status;
+use std::{path::PathBuf, sync::Arc};
+
+/// Source of asset files that loaders read from. Assets are addressed by
+/// their path relative to the root of the source, e.g. "sprites/hero.json".
+pub trait AssetSource: Send + Sync {
+    fn read(&self, path: &str) -> Result<Vec<u8>, Status>;
+
+    fn contains(&self, path: &str) -> bool;
+}
+
+/// Assets stored as loose files under a directory.
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(root: &str) -> Self {
+        DirectorySource {
+            root: PathBuf::from(root),
+        }
+    }
+}
+
+impl AssetSource for DirectorySource {
+    fn read(&self, path: &str) -> Result<Vec<u8>, Status> {
+        let filename = self.root.join(path);
+        std::fs::read(&filename).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                Status::not_found(format!("Asset '{}' not found", filename.display()))
+            }
+            _ => Status::new(&format!("Failed to read '{}'", filename.display()), e),
+        })
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.root.join(path).is_file()
+    }
+}
+
+/// Stacks several sources. An asset is read from the first source that
+/// contains it, so that e.g. a mod directory can override assets of the base
+/// game.
+pub struct LayeredSource {
+    layers: Vec<Arc<dyn AssetSource>>,
+}
+
+impl LayeredSource {
+    /// `layers` are ordered by precedence, highest first.
+    pub fn new(layers: Vec<Arc<dyn AssetSource>>) -> Self {
+        LayeredSource { layers }
+    }
+}
+
+impl AssetSource for LayeredSource {
+    fn read(&self, path: &str) -> Result<Vec<u8>, Status> {
+        match self.layers.iter().find(|layer| layer.contains(path)) {
+            Some(layer) => layer.read(path),
+            None => Err(Status::not_found(format!("Asset '{path}' not found"))),
+        }
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.layers.iter().any(|layer| layer.contains(path))
+    }
+}
+
+/// Resolves `path` relative to the directory of the asset `base`, e.g. a
+/// tileset path relative to its map, to a path relative to the assets root.
+pub fn resolve_path(base: &str, path: &str) -> String {
+    let mut segments: Vec<&str> = base.split('/').collect();
+    segments.pop();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upper_layers_override_lower_ones() {
+        let root = std::env::temp_dir().join(format!("crust_source_{}", std::process::id()));
+        let base = root.join("base");
+        let overlay = root.join("mod");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::create_dir_all(&overlay).unwrap();
+        std::fs::write(base.join("hero.json"), "base").unwrap();
+        std::fs::write(base.join("map.json"), "base").unwrap();
+        std::fs::write(overlay.join("hero.json"), "mod").unwrap();
+
+        let source = LayeredSource::new(vec![
+            Arc::new(DirectorySource::new(overlay.to_str().unwrap())),
+            Arc::new(DirectorySource::new(base.to_str().unwrap())),
+        ]);
+
+        assert_eq!(source.read("hero.json").unwrap(), b"mod");
+        assert_eq!(source.read("map.json").unwrap(), b"base");
+        assert!(matches!(source.read("foo.json"), Err(Status::NotFound(_))));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,14 +1,15 @@
-use super::{ResourceLoader, ResourceManager};
+use super::{read_tsx, AssetSource, ResourceLoader, ResourceManager};
 use crate::core::Status;
 use sdl2::rect::Rect;
 use specs::BitSet;
+use std::sync::Arc;
 
 pub type SpriteManager = ResourceManager<String, Sprite, SpriteLoader>;
 
 impl SpriteManager {
     pub fn get_collision_mask(&self, texture_id: &str, frame_index: usize) -> Option<&BitSet> {
         match self.get(texture_id) {
-            Some(sprite) => sprite.frames[frame_index].bitmask.as_ref(),
+            Some(sprite) => sprite.frames.get(frame_index)?.bitmask.as_ref(),
             None => None,
         }
     }
@@ -19,19 +20,32 @@ pub struct SpriteLoader;
 impl ResourceLoader<Sprite> for SpriteLoader {
     type Args = str;
 
-    fn load(&self, path: &str, resource: &str) -> Result<Sprite, Status> {
-        let filename = format!("{path}/{resource}.json");
-        let json = std::fs::read(&filename).expect(&format!("Failed to read '{filename}'"));
-        match serde_json::from_slice::<Sprite>(&json) {
-            Ok(sheet) => Ok(sheet),
-            Err(e) => Err(Status::new("Failed to parse sprite sheet: {}", e)),
+    fn load(&self, source: &dyn AssetSource, resource: &str) -> Result<Sprite, Status> {
+        let filename = format!("{resource}.json");
+        // Tilesets may also be read from Tiled's native format.
+        if !source.contains(&filename) && source.contains(&format!("{resource}.tsx")) {
+            return read_tsx(source, resource);
         }
+
+        let json = source.read(&filename)?;
+        serde_json::from_slice::<Sprite>(&json)
+            .map_err(|e| Status::new(&format!("Failed to parse sprite sheet '{filename}'"), e))
+    }
+
+    fn size(&self, sprite: &Sprite) -> usize {
+        let masks: usize = sprite
+            .frames
+            .iter()
+            .filter(|frame| frame.bitmask.is_some())
+            .map(|frame| (frame.bounding_box.width() * frame.bounding_box.height()) as usize / 8)
+            .sum();
+        std::mem::size_of::<Sprite>() + sprite.frames.len() * std::mem::size_of::<Frame>() + masks
     }
 }
 
 impl SpriteManager {
-    pub fn create(resource_path: &str) -> Self {
-        SpriteManager::new(resource_path, SpriteLoader {})
+    pub fn create(source: Arc<dyn AssetSource>) -> Self {
+        SpriteManager::new(source, SpriteLoader {})
     }
 
     pub fn get_box(&self, key: &str, index: usize) -> Option<Rect> {
@@ -48,7 +62,7 @@ impl SpriteManager {
 #[cfg(test)]
 impl SpriteManager {
     pub fn mock(sprites: Vec<Sprite>) -> Self {
-        let mut mgr = SpriteManager::new("", SpriteLoader {});
+        let mut mgr = SpriteManager::create(Arc::new(super::DirectorySource::new("")));
         mgr.set_resources(
             sprites
                 .into_iter()
@@ -71,6 +85,11 @@ use serde_with::serde_as;
 pub struct Sprite {
     pub texture_id: String,
     pub frames: Vec<Frame>,
+
+    // Per-tile data of sprites that are used as Tiled tilesets.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiles: Vec<TileData>,
 }
 
 #[serde_with::serde_as]
@@ -85,6 +104,24 @@ pub struct Frame {
     pub bitmask: Option<BitSet>,
 }
 
+/// Tile data as exported by Tiled in tilesets. Only tiles with extra data,
+/// e.g. animations, are listed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileData {
+    pub id: u32,
+
+    #[serde(default)]
+    pub animation: Vec<TileFrame>,
+}
+
+/// A frame of a tile animation that shows the tile `tileid` of the tileset
+/// for `duration` msec.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileFrame {
+    pub tileid: u32,
+    pub duration: u32,
+}
+
 struct BitMask;
 
 impl serde_with::SerializeAs<BitSet> for BitMask {
@@ -0,0 +1,27 @@
+use super::AssetSource;
+use crate::core::Status;
+use std::collections::HashMap;
+
+/// Assets held in memory, keyed by their path.
+#[derive(Default)]
+pub struct MemorySource(pub HashMap<String, Vec<u8>>);
+
+impl MemorySource {
+    pub fn with(mut self, path: &str, data: impl Into<Vec<u8>>) -> Self {
+        self.0.insert(path.to_owned(), data.into());
+        self
+    }
+}
+
+impl AssetSource for MemorySource {
+    fn read(&self, path: &str) -> Result<Vec<u8>, Status> {
+        self.0
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("Asset '{path}' not found")))
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.0.contains_key(path)
+    }
+}
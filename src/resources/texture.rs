@@ -1,6 +1,9 @@
-use super::{ResourceLoaderWithAnnotation, ResourceManagerWithAnnotation};
+use super::{AssetSource, ResourceLoaderWithAnnotation, ResourceManagerWithAnnotation};
 use crate::core::Status;
+use crate::crust::CacheStats;
+use log::debug;
 use sdl2::image::LoadTexture;
+use sdl2::pixels::Color;
 use sdl2::render::{Texture, TextureCreator};
 
 pub type TextureManager<'l, T> =
@@ -9,8 +12,50 @@ pub type TextureManager<'l, T> =
 impl<'l, T> ResourceLoaderWithAnnotation<'l, Texture<'l>> for TextureCreator<T> {
     type Args = str;
 
-    fn load(&'l self, path: &str, resource: &str) -> Result<Texture, Status> {
-        println!("Loading '{}/{}.png'", path, resource);
-        Ok(self.load_texture(format!("{}/{}.png", path, resource))?)
+    fn load(&'l self, source: &dyn AssetSource, resource: &str) -> Result<Texture, Status> {
+        debug!("Loading '{resource}.png'");
+        let filename = format!("{resource}.png");
+        let png = source.read(&filename)?;
+        self.load_texture_bytes(&png)
+            .map_err(|e| Status::internal(format!("Failed to decode '{filename}': {e}")))
+    }
+
+    fn size(&self, texture: &Texture<'l>) -> usize {
+        let query = texture.query();
+        (query.width * query.height) as usize * query.format.byte_size_per_pixel()
+    }
+}
+
+/// Textures that are no longer needed, e.g. by an unloaded scene. They are
+/// removed from the TextureManager at the end of the frame.
+#[derive(Default)]
+pub struct PendingTextureUnloads(pub Vec<String>);
+
+/// Cache stats of the TextureManager as of the last frame.
+#[derive(Default)]
+pub struct TextureStats(pub CacheStats);
+
+/// Sets the alpha modulation that is applied when copying the texture.
+///
+/// Textures are shared through the TextureManager as `Rc<Texture>`, which does
+/// not allow calling `Texture::set_alpha_mod()` that requires a mutable
+/// reference. The modulation is a plain render state on the SDL side, so it is
+/// safe to set it on a shared texture right before using it.
+pub fn set_texture_alpha(texture: &Texture, alpha: u8) {
+    unsafe {
+        sdl2::sys::SDL_SetTextureBlendMode(
+            texture.raw(),
+            sdl2::sys::SDL_BlendMode::SDL_BLENDMODE_BLEND,
+        );
+        sdl2::sys::SDL_SetTextureAlphaMod(texture.raw(), alpha);
+    }
+}
+
+/// Sets the colour and alpha modulation that is applied when copying the
+/// texture. See `set_texture_alpha()` regarding shared textures.
+pub fn set_texture_colour(texture: &Texture, colour: Color) {
+    set_texture_alpha(texture, colour.a);
+    unsafe {
+        sdl2::sys::SDL_SetTextureColorMod(texture.raw(), colour.r, colour.g, colour.b);
     }
 }
@@ -1,12 +1,14 @@
 use crate::core::Status;
+use sdl2::rect::Rect;
+use std::sync::Arc;
 
-use super::{ResourceLoader, ResourceManager};
+use super::{read_tmx, AssetSource, ResourceLoader, ResourceManager, TileGrid};
 
 pub type TileMapManager = ResourceManager<String, TileMap, TileMapLoader>;
 
 impl TileMapManager {
-    pub fn create(resource_path: &str) -> Self {
-        TileMapManager::new(resource_path, TileMapLoader {})
+    pub fn create(source: Arc<dyn AssetSource>) -> Self {
+        TileMapManager::new(source, TileMapLoader {})
     }
 }
 
@@ -15,13 +17,28 @@ pub struct TileMapLoader;
 impl ResourceLoader<TileMap> for TileMapLoader {
     type Args = str;
 
-    fn load(&self, path: &str, resource: &str) -> Result<TileMap, Status> {
-        let filename = format!("{path}/{resource}.json");
-        let json = std::fs::read(&filename).expect(&format!("Failed to read '{filename}'"));
-        match serde_json::from_slice::<TileMap>(&json) {
-            Ok(tilemap) => Ok(tilemap),
-            Err(e) => Err(Status::new("Failed to parse tile map: {}", e)),
+    fn load(&self, source: &dyn AssetSource, resource: &str) -> Result<TileMap, Status> {
+        let filename = format!("{resource}.json");
+        if !source.contains(&filename) && source.contains(&format!("{resource}.tmx")) {
+            return read_tmx(source, resource);
         }
+
+        let json = source.read(&filename)?;
+        serde_json::from_slice::<TileMap>(&json)
+            .map_err(|e| Status::new(&format!("Failed to parse tile map '{filename}'"), e))
+    }
+
+    fn size(&self, map: &TileMap) -> usize {
+        std::mem::size_of::<TileMap>()
+            + map
+                .layers
+                .iter()
+                .map(|layer| {
+                    std::mem::size_of::<Layer>()
+                        + layer.data.len() * std::mem::size_of::<u32>()
+                        + layer.objects.len() * std::mem::size_of::<Object>()
+                })
+                .sum::<usize>()
     }
 }
 
@@ -34,18 +51,149 @@ pub struct TileMap {
     pub width: u32,
     pub tileheight: u32,
     pub tilewidth: u32,
-    pub orientation: String,
+    pub orientation: Orientation,
     pub infinite: bool,
+
+    // Layout of staggered and hexagonal maps.
+    #[serde(default)]
+    pub staggeraxis: StaggerAxis,
+    #[serde(default)]
+    pub staggerindex: StaggerIndex,
+    #[serde(default)]
+    pub hexsidelength: u32,
+
     pub tilesets: Vec<TileSet>,
     pub layers: Vec<Layer>,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Orthogonal,
+    Isometric,
+    // Isometric tiles in rows or columns that are shifted every other time,
+    // like the tiles of hexagonal maps without sides.
+    Staggered,
+    Hexagonal,
+}
+
+/// Whether staggered and hexagonal maps shift rows or columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StaggerAxis {
+    X,
+    #[default]
+    Y,
+}
+
+/// Whether the odd or even rows or columns of staggered and hexagonal maps
+/// are shifted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StaggerIndex {
+    #[default]
+    Odd,
+    Even,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TileSet {
     pub firstgid: u32,
     pub source: String,
 }
 
+impl TileMap {
+    /// Returns the extent of the map in pixels. The extent of infinite maps is
+    /// that of their chunks, so it can have a negative origin.
+    pub fn bounds(&self) -> Rect {
+        self.grid().bounds(self.extent())
+    }
+
+    /// Returns the extent of the map in tiles.
+    pub fn extent(&self) -> Rect {
+        if !self.infinite {
+            return Rect::new(0, 0, self.width, self.height);
+        }
+
+        self.layers
+            .iter()
+            .flat_map(|layer| &layer.chunks)
+            .map(|chunk| Rect::new(chunk.x, chunk.y, chunk.width, chunk.height))
+            .reduce(|extent, chunk| extent.union(chunk))
+            .unwrap_or_else(|| Rect::new(0, 0, 0, 0))
+    }
+
+    /// Returns the layout of the map's tiles in the world.
+    pub fn grid(&self) -> TileGrid {
+        TileGrid::new(self)
+    }
+
+    /// Returns the tileset that the global tile id `gid` belongs to together
+    /// with the index of the tile in the tileset. Flip flags are ignored.
+    pub fn tileset(&self, gid: u32) -> Option<(&TileSet, u32)> {
+        let gid = gid & !TILE_FLAGS;
+        // Tiled lists tilesets in ascending order of their first gid.
+        self.tilesets
+            .iter()
+            .rev()
+            .find(|set| set.firstgid <= gid)
+            .map(|set| (set, gid - set.firstgid))
+    }
+}
+
+// Tiled stores how a tile is flipped in the top bits of its gid. The lowest of
+// them rotates tiles of hexagonal maps by 120 degrees, which is not supported.
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const TILE_FLAGS: u32 = 0xf000_0000;
+
+/// How a tile is flipped. A diagonal flip swaps the tile's axes and is applied
+/// before the horizontal and vertical flips, which together yields rotations
+/// by multiples of 90 degrees.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileFlip {
+    pub horizontal: bool,
+    pub vertical: bool,
+    pub diagonal: bool,
+}
+
+impl TileFlip {
+    pub fn from_gid(gid: u32) -> Self {
+        TileFlip {
+            horizontal: gid & FLIPPED_HORIZONTALLY != 0,
+            vertical: gid & FLIPPED_VERTICALLY != 0,
+            diagonal: gid & FLIPPED_DIAGONALLY != 0,
+        }
+    }
+
+    /// Returns the clockwise rotation in degrees and the horizontal and
+    /// vertical flips that produce the same result when the flips are applied
+    /// first, as SDL does when copying textures.
+    pub fn transform(&self) -> (f64, bool, bool) {
+        match (self.diagonal, self.horizontal, self.vertical) {
+            (false, horizontal, vertical) => (0.0, horizontal, vertical),
+            (true, false, false) => (90.0, false, true),
+            (true, true, false) => (90.0, false, false),
+            (true, false, true) => (270.0, false, false),
+            (true, true, true) => (90.0, true, false),
+        }
+    }
+}
+
+impl TileSet {
+    /// Name of the sprite sheet resource that backs the tileset.
+    pub fn resource(&self) -> Result<&str, Status> {
+        self.source.strip_suffix(".tsx").ok_or_else(|| {
+            Status::invalid_argument(format!(
+                "Tileset source '{}' is not a .tsx file",
+                self.source
+            ))
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Layer {
     #[serde(rename = "type")]
@@ -58,6 +206,11 @@ pub struct Layer {
     #[serde(default)]
     pub height: u32,
 
+    // Tile layers of infinite maps store their tiles in chunks instead of
+    // `data`.
+    #[serde(default)]
+    pub chunks: Vec<Chunk>,
+
     #[serde(default)]
     pub objects: Vec<Object>,
 
@@ -67,6 +220,62 @@ pub struct Layer {
     pub visible: bool,
     pub x: i32,
     pub y: i32,
+
+    #[serde(default)]
+    pub offsetx: f64,
+    #[serde(default)]
+    pub offsety: f64,
+    #[serde(default = "default_parallax")]
+    pub parallaxx: f64,
+    #[serde(default = "default_parallax")]
+    pub parallaxy: f64,
+}
+
+impl Layer {
+    /// Returns the non-empty tiles of a tile layer as (column, row, gid).
+    /// Tile coordinates can be negative in infinite maps.
+    pub fn tiles(&self) -> impl Iterator<Item = (i32, i32, u32)> + '_ {
+        grid_tiles(0, 0, self.width, &self.data).chain(
+            self.chunks
+                .iter()
+                .flat_map(|chunk| grid_tiles(chunk.x, chunk.y, chunk.width, &chunk.data)),
+        )
+    }
+}
+
+fn grid_tiles(
+    x: i32,
+    y: i32,
+    width: u32,
+    data: &[u32],
+) -> impl Iterator<Item = (i32, i32, u32)> + '_ {
+    let width = width.max(1);
+    data.iter()
+        .enumerate()
+        .filter(|(_, gid)| **gid > 0)
+        .map(move |(i, gid)| {
+            (
+                x + (i as u32 % width) as i32,
+                y + (i as u32 / width) as i32,
+                *gid,
+            )
+        })
+}
+
+/// A rectangular part of a tile layer in an infinite map, positioned in tile
+/// coordinates.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Chunk {
+    pub data: Vec<u32>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Tiled omits parallax factors from the exported json when they are 1.0.
+fn default_parallax() -> f64 {
+    1.0
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,10 +289,16 @@ pub struct Object {
     pub width: u32,
     pub height: u32,
 
+    // Set if the object is a tile. Tile objects are anchored at their bottom
+    // left corner.
+    #[serde(default)]
+    pub gid: Option<u32>,
+
     #[serde(default)]
     pub properties: Vec<ObjectProperty>,
 }
 
+/// Custom property of a Tiled object.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ObjectProperty {
@@ -92,4 +307,140 @@ pub enum ObjectProperty {
 
     #[serde(rename = "string")]
     StringType { name: String, value: String },
+
+    #[serde(rename = "int")]
+    IntType { name: String, value: i64 },
+
+    #[serde(rename = "float")]
+    FloatType { name: String, value: f64 },
+
+    // Formatted as "#AARRGGBB", or empty if unset.
+    #[serde(rename = "color")]
+    ColorType { name: String, value: String },
+
+    // Path relative to the file of the map.
+    #[serde(rename = "file")]
+    FileType { name: String, value: String },
+
+    // Id of another object in the map, or 0 if unset.
+    #[serde(rename = "object")]
+    ObjectType { name: String, value: u32 },
+
+    // Members of a custom class. Members that keep their default value are
+    // omitted.
+    #[serde(rename = "class")]
+    ClassType {
+        name: String,
+        #[serde(default)]
+        propertytype: String,
+        #[serde(with = "class_members")]
+        value: Vec<ObjectProperty>,
+    },
+}
+
+impl ObjectProperty {
+    pub fn name(&self) -> &str {
+        match self {
+            ObjectProperty::BoolType { name, .. }
+            | ObjectProperty::StringType { name, .. }
+            | ObjectProperty::IntType { name, .. }
+            | ObjectProperty::FloatType { name, .. }
+            | ObjectProperty::ColorType { name, .. }
+            | ObjectProperty::FileType { name, .. }
+            | ObjectProperty::ObjectType { name, .. }
+            | ObjectProperty::ClassType { name, .. } => name,
+        }
+    }
+}
+
+// Tiled's json format stores the members of a class by name without their
+// types, so their types are derived from their json values. Colours, files and
+// objects read as strings and ints.
+mod class_members {
+    use super::ObjectProperty;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde_json::{Map, Value};
+
+    pub fn serialize<S: Serializer>(
+        members: &[ObjectProperty],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        to_json(members).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<ObjectProperty>, D::Error> {
+        Ok(from_json(Map::deserialize(deserializer)?))
+    }
+
+    fn from_json(members: Map<String, Value>) -> Vec<ObjectProperty> {
+        members
+            .into_iter()
+            .filter_map(|(name, value)| {
+                Some(match value {
+                    Value::Bool(value) => ObjectProperty::BoolType { name, value },
+                    Value::Number(number) => match number.as_i64() {
+                        Some(value) => ObjectProperty::IntType { name, value },
+                        None => ObjectProperty::FloatType {
+                            name,
+                            value: number.as_f64()?,
+                        },
+                    },
+                    Value::String(value) => ObjectProperty::StringType { name, value },
+                    Value::Object(members) => ObjectProperty::ClassType {
+                        name,
+                        propertytype: String::default(),
+                        value: from_json(members),
+                    },
+                    Value::Array(_) | Value::Null => return None,
+                })
+            })
+            .collect()
+    }
+
+    fn to_json(members: &[ObjectProperty]) -> Map<String, Value> {
+        members
+            .iter()
+            .map(|member| {
+                let value = match member {
+                    ObjectProperty::BoolType { value, .. } => Value::from(*value),
+                    ObjectProperty::IntType { value, .. } => Value::from(*value),
+                    ObjectProperty::FloatType { value, .. } => Value::from(*value),
+                    ObjectProperty::ObjectType { value, .. } => Value::from(*value),
+                    ObjectProperty::StringType { value, .. }
+                    | ObjectProperty::ColorType { value, .. }
+                    | ObjectProperty::FileType { value, .. } => Value::from(value.as_str()),
+                    ObjectProperty::ClassType { value, .. } => Value::Object(to_json(value)),
+                };
+                (member.name().to_owned(), value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flip_flags_are_split_from_gids() {
+        let map: TileMap = serde_json::from_str(
+            r#"{
+                "width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+                "orientation": "orthogonal", "infinite": false,
+                "tilesets": [{"firstgid": 1, "source": "terrain.tsx"}],
+                "layers": []
+            }"#,
+        )
+        .unwrap();
+
+        let gid = 3 | FLIPPED_HORIZONTALLY | FLIPPED_DIAGONALLY;
+        assert_eq!(map.tileset(gid).unwrap().1, 2);
+
+        let flip = TileFlip::from_gid(gid);
+        assert!(flip.horizontal && flip.diagonal && !flip.vertical);
+        assert_eq!(flip.transform(), (90.0, false, false));
+        assert_eq!(TileFlip::from_gid(3).transform(), (0.0, false, false));
+    }
 }
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+/// Global scaling of simulated time. The time since the last frame that
+/// systems see is scaled by it.
+pub struct TimeScale {
+    pub scale: f64,
+    pub paused: bool,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale {
+            scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeScale {
+    pub fn apply(&self, duration: Duration) -> Duration {
+        match self.paused {
+            true => Duration::ZERO,
+            false => duration.mul_f64(self.scale),
+        }
+    }
+}
+
+// Time since the last simulation step ignoring the TimeScale. Screen space
+// nodes are animated with it so that they keep working while paused.
+pub struct UnscaledTime(pub Duration);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_time() {
+        let mut time_scale = TimeScale::default();
+        assert_eq!(
+            time_scale.apply(Duration::from_millis(10)),
+            Duration::from_millis(10)
+        );
+
+        time_scale.scale = 0.5;
+        assert_eq!(
+            time_scale.apply(Duration::from_millis(10)),
+            Duration::from_millis(5)
+        );
+
+        time_scale.paused = true;
+        assert_eq!(time_scale.apply(Duration::from_millis(10)), Duration::ZERO);
+    }
+}
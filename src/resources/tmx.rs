@@ -0,0 +1,589 @@
+use super::{
+    resolve_path, AssetSource, Chunk, Frame, Layer, Object, ObjectProperty, Orientation, Sprite,
+    StaggerAxis, StaggerIndex, TileData, TileFrame, TileMap, TileSet,
+};
+use crate::core::Status;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use roxmltree::{Document, Node};
+use sdl2::rect::Rect;
+use std::{io::Read, str::FromStr};
+
+// Support for the native XML formats of Tiled, .tmx maps and .tsx tilesets.
+//
+// Paths inside Tiled files are relative to the file that contains them. They
+// are resolved to resource paths relative to the assets root, so that a map
+// read from .tmx looks the same as one exported to json.
+
+/// Reads the tile map `resource` from its .tmx file.
+pub fn read_tmx(source: &dyn AssetSource, resource: &str) -> Result<TileMap, Status> {
+    let filename = format!("{resource}.tmx");
+    let xml = read_xml(source, &filename)?;
+    parse_tile_map(&xml, resource).map_err(|e| with_context(&filename, e))
+}
+
+/// Reads the tileset `resource` from its .tsx file as a sprite sheet whose
+/// frames are the tiles of the tileset.
+pub fn read_tsx(source: &dyn AssetSource, resource: &str) -> Result<Sprite, Status> {
+    let filename = format!("{resource}.tsx");
+    let xml = read_xml(source, &filename)?;
+    parse_tileset(&xml, resource).map_err(|e| with_context(&filename, e))
+}
+
+fn read_xml(source: &dyn AssetSource, filename: &str) -> Result<String, Status> {
+    String::from_utf8(source.read(filename)?)
+        .map_err(|e| Status::new(&format!("Failed to read '{filename}'"), e))
+}
+
+fn with_context(filename: &str, e: Status) -> Status {
+    Status::invalid_argument(format!("Failed to parse '{filename}': {}", e.message()))
+}
+
+fn parse_tile_map(xml: &str, resource: &str) -> Result<TileMap, Status> {
+    let document = Document::parse(xml).map_err(|e| Status::new("Invalid XML", e))?;
+    let map = document.root_element();
+    if !map.has_tag_name("map") {
+        return Err(Status::invalid_argument("Expected a <map> element"));
+    }
+
+    let mut tilesets = vec![];
+    let mut layers = vec![];
+    for node in map.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "tileset" => tilesets.push(parse_tileset_ref(node, resource)?),
+            "layer" => layers.push(parse_tile_layer(node)?),
+            "objectgroup" => layers.push(parse_object_group(node)?),
+            "group" => {
+                return Err(Status::invalid_argument(format!(
+                    "Group layer '{}' is not supported",
+                    node.attribute("name").unwrap_or_default()
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(TileMap {
+        height: required(map, "height")?,
+        width: required(map, "width")?,
+        tileheight: required(map, "tileheight")?,
+        tilewidth: required(map, "tilewidth")?,
+        orientation: match map.attribute("orientation").unwrap_or("orthogonal") {
+            "orthogonal" => Orientation::Orthogonal,
+            "isometric" => Orientation::Isometric,
+            "staggered" => Orientation::Staggered,
+            "hexagonal" => Orientation::Hexagonal,
+            orientation => {
+                return Err(Status::invalid_argument(format!(
+                    "Unsupported orientation '{orientation}'"
+                )))
+            }
+        },
+        infinite: attribute::<u32>(map, "infinite")?.unwrap_or_default() != 0,
+        staggeraxis: match map.attribute("staggeraxis") {
+            Some("x") => StaggerAxis::X,
+            Some("y") | None => StaggerAxis::Y,
+            Some(axis) => {
+                return Err(Status::invalid_argument(format!(
+                    "Unsupported stagger axis '{axis}'"
+                )))
+            }
+        },
+        staggerindex: match map.attribute("staggerindex") {
+            Some("even") => StaggerIndex::Even,
+            Some("odd") | None => StaggerIndex::Odd,
+            Some(index) => {
+                return Err(Status::invalid_argument(format!(
+                    "Unsupported stagger index '{index}'"
+                )))
+            }
+        },
+        hexsidelength: attribute(map, "hexsidelength")?.unwrap_or_default(),
+        tilesets,
+        layers,
+    })
+}
+
+fn parse_tileset_ref(node: Node, resource: &str) -> Result<TileSet, Status> {
+    let source = node.attribute("source").ok_or_else(|| {
+        Status::invalid_argument(
+            "Embedded tilesets are not supported, export the tileset to a .tsx file",
+        )
+    })?;
+    Ok(TileSet {
+        firstgid: required(node, "firstgid")?,
+        source: resolve_path(resource, source),
+    })
+}
+
+fn parse_tile_layer(node: Node) -> Result<Layer, Status> {
+    let mut layer = parse_layer(node, "tilelayer")?;
+    layer.width = required(node, "width")?;
+    layer.height = required(node, "height")?;
+    if let Some(data) = child(node, "data") {
+        parse_layer_data(&mut layer, data).map_err(|e| {
+            Status::invalid_argument(format!("Layer '{}': {}", layer.name, e.message()))
+        })?;
+    }
+    Ok(layer)
+}
+
+fn parse_object_group(node: Node) -> Result<Layer, Status> {
+    let mut layer = parse_layer(node, "objectgroup")?;
+    for object in node.children().filter(|child| child.has_tag_name("object")) {
+        layer.objects.push(Object {
+            // Tiled versions before 1.9 call the class of an object its type.
+            class: object
+                .attribute("class")
+                .or_else(|| object.attribute("type"))
+                .unwrap_or_default()
+                .to_owned(),
+            id: required(object, "id")?,
+            name: object.attribute("name").unwrap_or_default().to_owned(),
+            x: required::<f64>(object, "x")?.round() as i32,
+            y: required::<f64>(object, "y")?.round() as i32,
+            width: attribute::<f64>(object, "width")?
+                .unwrap_or_default()
+                .round() as u32,
+            height: attribute::<f64>(object, "height")?
+                .unwrap_or_default()
+                .round() as u32,
+            gid: attribute(object, "gid")?,
+            properties: parse_properties(object)?,
+        });
+    }
+    Ok(layer)
+}
+
+// Attributes that are common to all types of layers. Tiled omits attributes
+// that have their default value.
+fn parse_layer(node: Node, layer_type: &str) -> Result<Layer, Status> {
+    Ok(Layer {
+        layer_type: layer_type.to_owned(),
+        data: vec![],
+        width: 0,
+        height: 0,
+        chunks: vec![],
+        objects: vec![],
+        id: attribute(node, "id")?.unwrap_or_default(),
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        opacity: attribute(node, "opacity")?.unwrap_or(1.0),
+        visible: attribute::<u32>(node, "visible")?.unwrap_or(1) != 0,
+        x: 0,
+        y: 0,
+        offsetx: attribute(node, "offsetx")?.unwrap_or_default(),
+        offsety: attribute(node, "offsety")?.unwrap_or_default(),
+        parallaxx: attribute(node, "parallaxx")?.unwrap_or(1.0),
+        parallaxy: attribute(node, "parallaxy")?.unwrap_or(1.0),
+    })
+}
+
+// Layers of infinite maps split their data in chunks that share the encoding
+// of the data element.
+fn parse_layer_data(layer: &mut Layer, data: Node) -> Result<(), Status> {
+    for chunk in data.children().filter(|child| child.has_tag_name("chunk")) {
+        layer.chunks.push(Chunk {
+            data: decode_tiles(data, chunk)?,
+            x: required(chunk, "x")?,
+            y: required(chunk, "y")?,
+            width: required(chunk, "width")?,
+            height: required(chunk, "height")?,
+        });
+    }
+    if layer.chunks.is_empty() {
+        layer.data = decode_tiles(data, data)?;
+    }
+    Ok(())
+}
+
+fn decode_tiles(data: Node, tiles: Node) -> Result<Vec<u32>, Status> {
+    let text = tiles.text().unwrap_or_default().trim();
+    match data.attribute("encoding") {
+        // Deprecated format that lists every tile as an element.
+        None => tiles
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| Ok(attribute(tile, "gid")?.unwrap_or_default()))
+            .collect(),
+        Some("csv") => text
+            .split(',')
+            .map(|gid| {
+                gid.trim()
+                    .parse()
+                    .map_err(|_| Status::invalid_argument(format!("Invalid tile '{}'", gid.trim())))
+            })
+            .collect(),
+        Some("base64") => {
+            let bytes = STANDARD
+                .decode(text)
+                .map_err(|e| Status::new("Invalid base64 tile data", e))?;
+            let bytes = match data.attribute("compression") {
+                None => bytes,
+                Some("zlib") => decompress(ZlibDecoder::new(&bytes[..]))?,
+                Some("gzip") => decompress(GzDecoder::new(&bytes[..]))?,
+                Some(compression) => {
+                    return Err(Status::invalid_argument(format!(
+                        "Unsupported compression '{compression}'"
+                    )))
+                }
+            };
+            if bytes.len() % 4 != 0 {
+                return Err(Status::invalid_argument(format!(
+                    "Tile data of {} bytes is not a list of 32-bit tiles",
+                    bytes.len()
+                )));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        Some(encoding) => Err(Status::invalid_argument(format!(
+            "Unsupported encoding '{encoding}'"
+        ))),
+    }
+}
+
+fn decompress(mut decoder: impl Read) -> Result<Vec<u8>, Status> {
+    let mut bytes = vec![];
+    decoder
+        .read_to_end(&mut bytes)
+        .map_err(|e| Status::new("Failed to decompress tile data", e))?;
+    Ok(bytes)
+}
+
+fn parse_properties(node: Node) -> Result<Vec<ObjectProperty>, Status> {
+    let properties = match child(node, "properties") {
+        Some(properties) => properties,
+        None => return Ok(vec![]),
+    };
+
+    let mut result = vec![];
+    for property in properties
+        .children()
+        .filter(|child| child.has_tag_name("property"))
+    {
+        let name = required::<String>(property, "name")?;
+        // Multi-line strings are stored as text instead of an attribute.
+        let value = property
+            .attribute("value")
+            .or_else(|| property.text())
+            .unwrap_or_default();
+        result.push(match property.attribute("type").unwrap_or("string") {
+            "bool" => ObjectProperty::BoolType {
+                value: value == "true",
+                name,
+            },
+            "string" => ObjectProperty::StringType {
+                value: value.to_owned(),
+                name,
+            },
+            "int" => ObjectProperty::IntType {
+                value: parse_value(&name, value)?,
+                name,
+            },
+            "float" => ObjectProperty::FloatType {
+                value: parse_value(&name, value)?,
+                name,
+            },
+            "color" => ObjectProperty::ColorType {
+                value: value.to_owned(),
+                name,
+            },
+            "file" => ObjectProperty::FileType {
+                value: value.to_owned(),
+                name,
+            },
+            "object" => ObjectProperty::ObjectType {
+                value: parse_value(&name, value)?,
+                name,
+            },
+            // Members of classes are nested properties. Members that keep
+            // their default value are omitted.
+            "class" => ObjectProperty::ClassType {
+                propertytype: property
+                    .attribute("propertytype")
+                    .unwrap_or_default()
+                    .to_owned(),
+                value: parse_properties(property)?,
+                name,
+            },
+            property_type => {
+                return Err(Status::invalid_argument(format!(
+                    "Property '{name}' has unknown type '{property_type}'"
+                )))
+            }
+        });
+    }
+    Ok(result)
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, Status> {
+    value.parse().map_err(|_| {
+        Status::invalid_argument(format!("Property '{name}' has invalid value '{value}'"))
+    })
+}
+
+fn parse_tileset(xml: &str, resource: &str) -> Result<Sprite, Status> {
+    let document = Document::parse(xml).map_err(|e| Status::new("Invalid XML", e))?;
+    let tileset = document.root_element();
+    if !tileset.has_tag_name("tileset") {
+        return Err(Status::invalid_argument("Expected a <tileset> element"));
+    }
+
+    let image = child(tileset, "image").ok_or_else(|| {
+        Status::invalid_argument("Tilesets that are collections of images are not supported")
+    })?;
+    let image_source = resolve_path(resource, &required::<String>(image, "source")?);
+    let texture_id = image_source.strip_suffix(".png").ok_or_else(|| {
+        Status::invalid_argument(format!("Tileset image '{image_source}' is not a .png file"))
+    })?;
+
+    let tile_width: u32 = required(tileset, "tilewidth")?;
+    let tile_height: u32 = required(tileset, "tileheight")?;
+    let spacing: u32 = attribute(tileset, "spacing")?.unwrap_or_default();
+    let margin: u32 = attribute(tileset, "margin")?.unwrap_or_default();
+    if tile_width == 0 || tile_height == 0 {
+        return Err(Status::invalid_argument("Tiles have zero size"));
+    }
+
+    // Older versions of Tiled do not write the tileset dimensions, so they are
+    // derived from the image size.
+    let fit = |image_size: u32, tile_size: u32| {
+        (image_size.saturating_sub(2 * margin) + spacing) / (tile_size + spacing)
+    };
+    let columns = match attribute(tileset, "columns")? {
+        Some(columns) if columns > 0 => columns,
+        _ => fit(required(image, "width")?, tile_width),
+    };
+    if columns == 0 {
+        return Err(Status::invalid_argument(
+            "Tileset image is narrower than a tile",
+        ));
+    }
+    let tile_count = match attribute(tileset, "tilecount")? {
+        Some(tile_count) => tile_count,
+        None => columns * fit(required(image, "height")?, tile_height),
+    };
+
+    let frames = (0..tile_count)
+        .map(|i| Frame {
+            bounding_box: Rect::new(
+                (margin + (i % columns) * (tile_width + spacing)) as i32,
+                (margin + (i / columns) * (tile_height + spacing)) as i32,
+                tile_width,
+                tile_height,
+            ),
+            bitmask: None,
+        })
+        .collect();
+
+    let mut tiles = vec![];
+    for tile in tileset
+        .children()
+        .filter(|child| child.has_tag_name("tile"))
+    {
+        let animation = match child(tile, "animation") {
+            Some(animation) => animation
+                .children()
+                .filter(|child| child.has_tag_name("frame"))
+                .map(|frame| {
+                    Ok(TileFrame {
+                        tileid: required(frame, "tileid")?,
+                        duration: required(frame, "duration")?,
+                    })
+                })
+                .collect::<Result<_, Status>>()?,
+            None => vec![],
+        };
+        tiles.push(TileData {
+            id: required(tile, "id")?,
+            animation,
+        });
+    }
+
+    Ok(Sprite {
+        texture_id: texture_id.to_owned(),
+        frames,
+        tiles,
+    })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag))
+}
+
+fn attribute<T: FromStr>(node: Node, name: &str) -> Result<Option<T>, Status> {
+    match node.attribute(name) {
+        Some(value) => value.parse().map(Some).map_err(|_| {
+            Status::invalid_argument(format!(
+                "<{}> has invalid {name} '{value}'",
+                node.tag_name().name()
+            ))
+        }),
+        None => Ok(None),
+    }
+}
+
+fn required<T: FromStr>(node: Node, name: &str) -> Result<T, Status> {
+    attribute(node, name)?.ok_or_else(|| {
+        Status::invalid_argument(format!("<{}> is missing {name}", node.tag_name().name()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tileset_frames_account_for_margin_and_spacing() {
+        let sprite = parse_tileset(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tileset name="terrain" tilewidth="16" tileheight="16" spacing="2" margin="1" tilecount="4" columns="2">
+              <image source="../images/terrain.png" width="36" height="36"/>
+              <tile id="3">
+                <animation>
+                  <frame tileid="3" duration="100"/>
+                  <frame tileid="2" duration="100"/>
+                </animation>
+              </tile>
+            </tileset>"#,
+            "tilesets/terrain",
+        )
+        .unwrap();
+
+        assert_eq!(sprite.texture_id, "images/terrain");
+        assert_eq!(sprite.frames.len(), 4);
+        assert_eq!(sprite.frames[3].bounding_box, Rect::new(19, 19, 16, 16));
+        assert_eq!(sprite.tiles[0].animation[1].tileid, 2);
+    }
+
+    #[test]
+    fn tilesets_without_tiles_are_rejected() {
+        let tileset = |attributes: &str, image_width: u32| {
+            parse_tileset(
+                &format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <tileset name="terrain" {attributes} tilecount="4">
+                      <image source="terrain.png" width="{image_width}" height="32"/>
+                    </tileset>"#
+                ),
+                "terrain",
+            )
+        };
+
+        assert!(tileset(r#"tilewidth="16" tileheight="16""#, 32).is_ok());
+        assert!(tileset(r#"tilewidth="0" tileheight="16""#, 32).is_err());
+        assert!(tileset(r#"tilewidth="16" tileheight="16""#, 8).is_err());
+    }
+
+    #[test]
+    fn tile_map_layers_are_decoded() {
+        let map = parse_tile_map(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+              <tileset firstgid="1" source="../tilesets/terrain.tsx"/>
+              <layer id="1" name="ground" width="2" height="2">
+                <data encoding="csv">
+            1,2,
+            0,4
+            </data>
+              </layer>
+              <layer id="2" name="overlay" width="2" height="2" opacity="0.5">
+                <data encoding="base64">AwAAAAAAAAAAAAAAAQAAAA==</data>
+              </layer>
+              <objectgroup id="3" name="objects">
+                <object id="1" name="door" type="portal" x="8.4" y="16" width="16" height="16">
+                  <properties>
+                    <property name="rigid_body" type="bool" value="true"/>
+                  </properties>
+                </object>
+              </objectgroup>
+            </map>"#,
+            "maps/level1",
+        )
+        .unwrap();
+
+        assert_eq!(map.tilesets[0].source, "tilesets/terrain.tsx");
+        assert_eq!(map.layers[0].data, vec![1, 2, 0, 4]);
+        assert_eq!(map.layers[1].data, vec![3, 0, 0, 1]);
+        assert_eq!(map.layers[1].opacity, 0.5);
+        assert_eq!(map.layers[2].objects[0].class, "portal");
+        assert_eq!(map.layers[2].objects[0].x, 8);
+    }
+
+    #[test]
+    fn unknown_stagger_layouts_are_rejected() {
+        let map = |stagger: &str| {
+            parse_tile_map(
+                &format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <map orientation="staggered" width="1" height="1" tilewidth="16" tileheight="8" infinite="0" {stagger}/>"#
+                ),
+                "level",
+            )
+        };
+
+        assert_eq!(
+            map(r#"staggeraxis="x" staggerindex="even""#)
+                .unwrap()
+                .staggeraxis,
+            StaggerAxis::X
+        );
+        assert!(map(r#"staggeraxis="z""#).is_err());
+        assert!(map(r#"staggerindex="first""#).is_err());
+    }
+
+    #[test]
+    fn group_layers_are_rejected() {
+        let map = parse_tile_map(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map orientation="orthogonal" width="1" height="1" tilewidth="16" tileheight="16" infinite="0">
+              <group id="1" name="background">
+                <layer id="2" name="ground" width="1" height="1">
+                  <data encoding="csv">0</data>
+                </layer>
+              </group>
+            </map>"#,
+            "level",
+        );
+
+        assert!(map.is_err());
+    }
+
+    #[test]
+    fn truncated_base64_tiles_are_rejected() {
+        let map = parse_tile_map(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map orientation="orthogonal" width="1" height="1" tilewidth="16" tileheight="16" infinite="0">
+              <layer id="1" name="ground" width="1" height="1">
+                <data encoding="base64">AwAA</data>
+              </layer>
+            </map>"#,
+            "level",
+        );
+
+        assert!(map.is_err());
+    }
+
+    #[test]
+    fn infinite_map_chunks_extend_bounds() {
+        let map = parse_tile_map(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map orientation="orthogonal" width="4" height="2" tilewidth="16" tileheight="16" infinite="1">
+              <layer id="1" name="ground" width="4" height="2">
+                <data encoding="csv">
+                  <chunk x="-2" y="-1" width="2" height="1">1,0</chunk>
+                  <chunk x="0" y="0" width="2" height="1">0,2</chunk>
+                </data>
+              </layer>
+            </map>"#,
+            "level",
+        )
+        .unwrap();
+
+        let tiles: Vec<_> = map.layers[0].tiles().collect();
+        assert_eq!(tiles, vec![(-2, -1, 1), (1, 0, 2)]);
+        assert_eq!(map.bounds(), Rect::new(-32, -16, 64, 32));
+    }
+}
@@ -0,0 +1,226 @@
+use super::{Sprite, TileMap};
+use specs::hibitset::BitSetLike;
+
+// Consistency checks of assets that are not covered by parsing alone. Each
+// check returns a description of every problem found, so that all of them can
+// be reported at once.
+
+/// Checks that bitmasks fit their frame's bounding box and that tile data only
+/// refers to existing frames.
+pub fn validate_sprite(sprite: &Sprite) -> Vec<String> {
+    let mut problems = vec![];
+    for (i, frame) in sprite.frames.iter().enumerate() {
+        let bounding_box = frame.bounding_box;
+        let pixels = bounding_box.width() * bounding_box.height();
+        if let Some(bitmask) = &frame.bitmask {
+            if let Some(bit) = bitmask.iter().filter(|bit| *bit >= pixels).max() {
+                problems.push(format!(
+                    "frames[{i}]: bitmask has bit {bit} outside of the {}x{} bounding box",
+                    bounding_box.width(),
+                    bounding_box.height()
+                ));
+            }
+        }
+    }
+
+    for tile in &sprite.tiles {
+        if tile.id as usize >= sprite.frames.len() {
+            problems.push(format!(
+                "tiles[id={}]: tile exceeds the {} frames",
+                tile.id,
+                sprite.frames.len()
+            ));
+        }
+        for (i, frame) in tile.animation.iter().enumerate() {
+            if frame.tileid as usize >= sprite.frames.len() {
+                problems.push(format!(
+                    "tiles[id={}].animation[{i}]: frame {} exceeds the {} frames",
+                    tile.id,
+                    frame.tileid,
+                    sprite.frames.len()
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Checks that tilesets can be resolved with `tileset_frames`, which returns
+/// the number of frames of a tileset resource if it exists, and that tile
+/// layers and tile objects only refer to existing tiles.
+pub fn validate_tile_map(
+    map: &TileMap,
+    tileset_frames: impl Fn(&str) -> Option<usize>,
+) -> Vec<String> {
+    let mut problems = vec![];
+    for (i, set) in map.tilesets.iter().enumerate() {
+        match set.resource() {
+            Ok(resource) if tileset_frames(resource).is_none() => {
+                problems.push(format!("tilesets[{i}]: tileset '{resource}' not found"))
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(format!("tilesets[{i}]: {}", e.message())),
+        }
+    }
+
+    for layer in map
+        .layers
+        .iter()
+        .filter(|layer| layer.layer_type == "tilelayer")
+    {
+        if layer.chunks.is_empty() && layer.data.len() != (layer.width * layer.height) as usize {
+            problems.push(format!(
+                "layer '{}': has {} tiles instead of {}x{}",
+                layer.name,
+                layer.data.len(),
+                layer.width,
+                layer.height
+            ));
+        }
+        for chunk in &layer.chunks {
+            if chunk.data.len() != (chunk.width * chunk.height) as usize {
+                problems.push(format!(
+                    "layer '{}': chunk at ({}, {}) has {} tiles instead of {}x{}",
+                    layer.name,
+                    chunk.x,
+                    chunk.y,
+                    chunk.data.len(),
+                    chunk.width,
+                    chunk.height
+                ));
+            }
+        }
+
+        for (x, y, tile_id) in layer.tiles() {
+            let (tileset, tile_index) = match map.tileset(tile_id) {
+                Some(tile) => tile,
+                None => {
+                    problems.push(format!(
+                        "layer '{}': tile {tile_id} at ({x}, {y}) is not in any tileset",
+                        layer.name
+                    ));
+                    continue;
+                }
+            };
+            let frames = match tileset.resource().ok().and_then(&tileset_frames) {
+                Some(frames) => frames,
+                // Reported with the tileset.
+                None => continue,
+            };
+            if tile_index as usize >= frames {
+                problems.push(format!(
+                    "layer '{}': tile {tile_id} at ({x}, {y}) exceeds the {frames} tiles of '{}'",
+                    layer.name, tileset.source
+                ));
+            }
+        }
+    }
+
+    for layer in map
+        .layers
+        .iter()
+        .filter(|layer| layer.layer_type == "objectgroup")
+    {
+        for object in &layer.objects {
+            let gid = match object.gid {
+                Some(gid) => gid,
+                None => continue,
+            };
+            match map.tileset(gid) {
+                Some((tileset, tile_index)) => {
+                    if let Some(frames) = tileset.resource().ok().and_then(&tileset_frames) {
+                        if tile_index as usize >= frames {
+                            problems.push(format!(
+                                "layer '{}': object {} has tile {gid} that exceeds the {frames} tiles of '{}'",
+                                layer.name, object.id, tileset.source
+                            ));
+                        }
+                    }
+                }
+                None => problems.push(format!(
+                    "layer '{}': object {} has tile {gid} that is not in any tileset",
+                    layer.name, object.id
+                )),
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{Frame, TileData, TileFrame};
+    use sdl2::rect::Rect;
+
+    fn sprite(bitmask: Vec<u32>, tiles: Vec<TileData>) -> Sprite {
+        Sprite {
+            texture_id: "terrain".to_owned(),
+            frames: vec![Frame {
+                bounding_box: Rect::new(0, 0, 4, 2),
+                bitmask: Some(bitmask.into_iter().collect()),
+            }],
+            tiles,
+        }
+    }
+
+    #[test]
+    fn reports_bitmasks_outside_of_the_frame() {
+        assert!(validate_sprite(&sprite(vec![0, 7], vec![])).is_empty());
+        assert_eq!(
+            validate_sprite(&sprite(vec![0, 8], vec![])),
+            vec!["frames[0]: bitmask has bit 8 outside of the 4x2 bounding box"]
+        );
+    }
+
+    #[test]
+    fn reports_tile_animation_frames_out_of_range() {
+        let tiles = vec![TileData {
+            id: 0,
+            animation: vec![
+                TileFrame {
+                    tileid: 0,
+                    duration: 100,
+                },
+                TileFrame {
+                    tileid: 1,
+                    duration: 100,
+                },
+            ],
+        }];
+        assert_eq!(
+            validate_sprite(&sprite(vec![], tiles)),
+            vec!["tiles[id=0].animation[1]: frame 1 exceeds the 1 frames"]
+        );
+    }
+
+    #[test]
+    fn reports_tiles_outside_of_tilesets() {
+        let map: TileMap = serde_json::from_str(
+            r#"{
+                "width": 3, "height": 1, "tilewidth": 16, "tileheight": 16,
+                "orientation": "orthogonal", "infinite": false,
+                "tilesets": [
+                    {"firstgid": 1, "source": "terrain.tsx"},
+                    {"firstgid": 3, "source": "items.tsx"}
+                ],
+                "layers": [{
+                    "type": "tilelayer", "id": 1, "name": "ground",
+                    "width": 3, "height": 1, "data": [2, 3, 5],
+                    "opacity": 1.0, "visible": true, "x": 0, "y": 0
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let problems = validate_tile_map(&map, |resource| match resource {
+            "terrain" => Some(2),
+            "items" => Some(2),
+            _ => None,
+        });
+        assert_eq!(
+            problems,
+            vec!["layer 'ground': tile 5 at (2, 0) exceeds the 2 tiles of 'items.tsx'"]
+        );
+    }
+}
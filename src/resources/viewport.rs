@@ -4,4 +4,6 @@ pub struct Viewport(pub Rect);
 
 pub struct WorldSize(pub Rect);
 
+// Size of the area the game is rendered at. When a logical resolution is
+// configured this is the logical size and not the size of the actual window.
 pub struct WindowSize(pub Rect);
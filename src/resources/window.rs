@@ -0,0 +1,12 @@
+use crate::crust::FullscreenMode;
+
+/// Changes to the window requested during the frame. They are applied by Core
+/// that owns the window.
+#[derive(Default)]
+pub struct PendingWindowChanges(pub Vec<WindowChange>);
+
+#[derive(Debug)]
+pub enum WindowChange {
+    Fullscreen(FullscreenMode),
+    Resize(u32, u32),
+}
@@ -1,5 +1,8 @@
+mod object_properties;
 mod scene;
 mod scene_builder;
 mod scene_manager;
+mod tile_animation;
 
 pub use scene_manager::SceneManager;
+pub use tile_animation::{TileAnimation, TileAnimations};
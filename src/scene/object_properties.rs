@@ -0,0 +1,649 @@
+use crate::{
+    action::{ACTION_QUEUE, INDEX},
+    components::{
+        Animation, Collisions, Depth, Id, Position, Properties, RigidBody, Rotation, Scaling,
+        SpriteInfo, Velocity,
+    },
+    core::Status,
+    crust::{
+        event, property_value, AnimationScript, Box, CollisionAction, PropertyClass, PropertyValue,
+        RgBa, SceneNodeEvent,
+    },
+    resources::{
+        resolve_path, AssetSource, Object, ObjectProperty, Orientation, SpriteManager, TileFlip,
+        TileGrid, TileMap,
+    },
+};
+use log::warn;
+use prost::Message;
+use sdl2::rect::Rect;
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// Components of a scene node that are derived from a Tiled map object and
+/// its custom properties. Tile objects are drawn with their tile. Recognised
+/// properties are:
+///
+/// * `sprite` (string or file) and `frame` (int): sprite frame that the node
+///   is drawn with. Objects without a size take the size of the frame.
+/// * `z` (int): depth of the node.
+/// * `rigid_body` (bool): makes the node a rigid body.
+/// * `script` (string or file): file with an encoded AnimationScript that
+///   starts playing on the node.
+/// * `on_collision` (string): comma separated ids of scene nodes or sprites.
+///   Collisions with them are reported with collision events.
+///
+/// Other properties are kept in the node's Properties for the host to query.
+pub struct ObjectComponents {
+    id: String,
+    class: String,
+    position: Rect,
+    depth: i32,
+    sprite: Option<SpriteInfo>,
+    rigid_body: bool,
+    animation: Option<Animation>,
+    collisions: Option<Collisions>,
+    properties: Properties,
+}
+
+/// Context for resolving the properties of the objects of a map.
+pub struct MapContext<'a> {
+    // Map resource that relative file paths are resolved against.
+    pub resource: &'a str,
+    pub map: &'a TileMap,
+    pub grid: TileGrid,
+    pub source: &'a dyn AssetSource,
+    // Names of the map's objects by object id.
+    pub object_names: HashMap<u32, &'a str>,
+}
+
+impl ObjectComponents {
+    pub fn new(object: &Object, context: &MapContext, world: &World) -> Result<Self, Status> {
+        let mut components = ObjectComponents {
+            id: object.name.clone(),
+            class: object.class.clone(),
+            position: match object.gid {
+                Some(_) => Rect::new(object.x, object.y, object.width, object.height),
+                None => object_rect(object, &context.grid),
+            },
+            depth: 0,
+            sprite: None,
+            rigid_body: false,
+            animation: None,
+            collisions: None,
+            properties: Properties::default(),
+        };
+
+        let mut frame_index = 0;
+        let mut sprite = None;
+        let flip = object.gid.map(TileFlip::from_gid).unwrap_or_default();
+        if let Some(gid) = object.gid {
+            let (tileset, tile_index) = context.map.tileset(gid).ok_or_else(|| {
+                Status::invalid_argument(format!(
+                    "Object '{}' (id {}): tile {gid} is not in any tileset",
+                    object.name, object.id
+                ))
+            })?;
+            sprite = Some(tileset.resource()?.to_owned());
+            frame_index = tile_index as usize;
+        }
+        for property in &object.properties {
+            components
+                .apply(property, context, &mut sprite, &mut frame_index)
+                .map_err(|e| {
+                    Status::invalid_argument(format!(
+                        "Object '{}' (id {}), property '{}': {}",
+                        object.name,
+                        object.id,
+                        property.name(),
+                        e.message()
+                    ))
+                })?;
+        }
+
+        if let Some(sprite) = sprite {
+            let mut sprite_manager = world.write_resource::<SpriteManager>();
+            sprite_manager.load(sprite.as_str())?;
+            let bounding_box = sprite_manager
+                .get_box(&sprite, frame_index)
+                .ok_or_else(|| {
+                    Status::invalid_argument(format!(
+                        "Object '{}' (id {}): frame {frame_index} exceeds the frames of '{sprite}'",
+                        object.name, object.id
+                    ))
+                })?;
+            if object.width == 0 || object.height == 0 {
+                components.position.set_width(bounding_box.width());
+                components.position.set_height(bounding_box.height());
+            }
+            if object.gid.is_some() {
+                // Tile objects are anchored at their bottom left corner, or
+                // their bottom centre in isometric maps.
+                let anchor = context.grid.object_to_world(object.x, object.y);
+                let (width, height) = (
+                    components.position.width() as i32,
+                    components.position.height() as i32,
+                );
+                let left = match context.grid.orientation() {
+                    Orientation::Isometric => anchor.x() - width / 2,
+                    _ => anchor.x(),
+                };
+                components.position.reposition((left, anchor.y() - height));
+            }
+            // Tilesets read from .tsx files name the texture after their image.
+            let texture_id = sprite_manager.get(&sprite).unwrap().texture_id.clone();
+            components.sprite = Some(SpriteInfo {
+                sprite_ref: sprite_manager.acquire(sprite.as_str()),
+                sprite_id: sprite,
+                texture_id,
+                frame_index,
+                bounding_box,
+                flip,
+            });
+        }
+
+        Ok(components)
+    }
+
+    fn apply(
+        &mut self,
+        property: &ObjectProperty,
+        context: &MapContext,
+        sprite: &mut Option<String>,
+        frame_index: &mut usize,
+    ) -> Result<(), Status> {
+        match (property.name(), property) {
+            ("sprite", ObjectProperty::StringType { value, .. }) => {
+                *sprite = Some(value.clone());
+            }
+            ("sprite", ObjectProperty::FileType { value, .. }) => {
+                let path = resolve_path(context.resource, value);
+                *sprite = Some(match path.rsplit_once('.') {
+                    Some((resource, _)) => resource.to_owned(),
+                    None => path,
+                });
+            }
+            ("frame", ObjectProperty::IntType { value, .. }) => {
+                *frame_index = usize::try_from(*value)
+                    .map_err(|_| Status::invalid_argument("Frame cannot be negative"))?;
+            }
+            ("z", ObjectProperty::IntType { value, .. }) => {
+                self.depth = i32::try_from(*value)
+                    .map_err(|_| Status::invalid_argument("Depth is out of range"))?;
+            }
+            ("rigid_body", ObjectProperty::BoolType { value, .. }) => {
+                self.rigid_body = *value;
+            }
+            ("script", ObjectProperty::StringType { value, .. }) => {
+                self.animation = Some(load_script(context.source, value)?);
+            }
+            ("script", ObjectProperty::FileType { value, .. }) => {
+                let path = resolve_path(context.resource, value);
+                self.animation = Some(load_script(context.source, &path)?);
+            }
+            ("on_collision", ObjectProperty::StringType { value, .. }) => {
+                self.collisions = Some(Collisions {
+                    on_collision: value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|other_id| !other_id.is_empty())
+                        .map(|other_id| CollisionAction {
+                            scene_node_id: self.id.clone(),
+                            other_id: other_id.to_owned(),
+                            action: vec![],
+                        })
+                        .collect(),
+                });
+            }
+            ("sprite" | "frame" | "z" | "rigid_body" | "script" | "on_collision", _) => {
+                return Err(Status::invalid_argument("Property has the wrong type"));
+            }
+            (name, property) => {
+                self.properties
+                    .0
+                    .insert(name.to_owned(), property_value(property, context)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the scene node. Named nodes are added to the index so that
+    /// actions can refer to them, and announced with a scene_node_created
+    /// event. Unnamed objects, e.g. decorations, are neither: the index and
+    /// events identify nodes by their id, which they lack, and several of
+    /// them would share the empty one.
+    pub fn build(self, world: &mut World) -> Entity {
+        let mut builder = world
+            .create_entity()
+            .with(Id(self.id.clone()))
+            .with(Position(self.position))
+            .with(Depth(self.depth))
+            .with(Velocity::default())
+            .with(Rotation::default())
+            .with(Scaling::default())
+            .with(self.properties);
+        if let Some(sprite) = self.sprite {
+            builder = builder.with(sprite);
+        }
+        if self.rigid_body {
+            builder = builder.with(RigidBody {});
+        }
+        if let Some(animation) = self.animation {
+            builder = builder.with(animation);
+        }
+        if let Some(collisions) = self.collisions {
+            builder = builder.with(collisions);
+        }
+        let entity = builder.build();
+
+        if !self.id.is_empty() {
+            INDEX.with(|index| {
+                if let Some(index) = &mut *index.borrow_mut() {
+                    match index.find_entity(&self.id) {
+                        Some(_) => warn!("Scene node '{}' already exists", self.id),
+                        None => index.add_entity(&self.id, entity.id()),
+                    }
+                }
+            });
+            ACTION_QUEUE.with(|queue| {
+                if let Some(queue) = &*queue.borrow() {
+                    queue.emit(
+                        self.id.clone(),
+                        event::Event::SceneNodeCreated(SceneNodeEvent {
+                            scene_node_id: self.id,
+                            class: self.class,
+                            position: Some(Box {
+                                left: self.position.x(),
+                                top: self.position.y(),
+                                width: self.position.width(),
+                                height: self.position.height(),
+                            }),
+                        }),
+                    );
+                }
+            });
+        }
+        entity
+    }
+}
+
+// Rectangles of isometric maps are diamonds in the world. Scene nodes take
+// their bounding box.
+fn object_rect(object: &Object, grid: &TileGrid) -> Rect {
+    let (right, bottom) = (
+        object.x + object.width as i32,
+        object.y + object.height as i32,
+    );
+    let corners = [
+        (object.x, object.y),
+        (right, object.y),
+        (object.x, bottom),
+        (right, bottom),
+    ]
+    .map(|(x, y)| grid.object_to_world(x, y));
+    let left = corners
+        .iter()
+        .map(|corner| corner.x())
+        .min()
+        .unwrap_or_default();
+    let top = corners
+        .iter()
+        .map(|corner| corner.y())
+        .min()
+        .unwrap_or_default();
+    let right = corners
+        .iter()
+        .map(|corner| corner.x())
+        .max()
+        .unwrap_or_default();
+    let bottom = corners
+        .iter()
+        .map(|corner| corner.y())
+        .max()
+        .unwrap_or_default();
+    Rect::new(left, top, (right - left) as u32, (bottom - top) as u32)
+}
+
+fn load_script(source: &dyn AssetSource, path: &str) -> Result<Animation, Status> {
+    let script = AnimationScript::decode(&source.read(path)?[..])
+        .map_err(|e| Status::new(&format!("Failed to decode script '{path}'"), e))?;
+    Ok(Animation::new(script))
+}
+
+fn property_value(
+    property: &ObjectProperty,
+    context: &MapContext,
+) -> Result<PropertyValue, Status> {
+    let value = match property {
+        ObjectProperty::BoolType { value, .. } => property_value::Value::BoolValue(*value),
+        ObjectProperty::StringType { value, .. } => {
+            property_value::Value::StringValue(value.clone())
+        }
+        ObjectProperty::IntType { value, .. } => property_value::Value::IntValue(*value),
+        ObjectProperty::FloatType { value, .. } => property_value::Value::FloatValue(*value),
+        ObjectProperty::ColorType { value, .. } => match value.is_empty() {
+            true => return Ok(PropertyValue { value: None }),
+            false => property_value::Value::ColorValue(parse_color(value)?),
+        },
+        ObjectProperty::FileType { value, .. } => property_value::Value::FileValue(value.clone()),
+        ObjectProperty::ObjectType { value, .. } => property_value::Value::ObjectValue(
+            context
+                .object_names
+                .get(value)
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+        ),
+        ObjectProperty::ClassType {
+            propertytype,
+            value,
+            ..
+        } => property_value::Value::ClassValue(PropertyClass {
+            r#type: propertytype.clone(),
+            members: value
+                .iter()
+                .map(|member| Ok((member.name().to_owned(), property_value(member, context)?)))
+                .collect::<Result<_, Status>>()?,
+        }),
+    };
+    Ok(PropertyValue { value: Some(value) })
+}
+
+// Tiled formats colours as "#AARRGGBB", or "#RRGGBB" if they are opaque.
+fn parse_color(value: &str) -> Result<RgBa, Status> {
+    let invalid = || Status::invalid_argument(format!("Invalid colour '{value}'"));
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    let argb = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    let argb = match hex.len() {
+        8 => argb,
+        6 => 0xff000000 | argb,
+        _ => return Err(invalid()),
+    };
+    Ok(RgBa {
+        alpha: (argb >> 24) as i32,
+        red: ((argb >> 16) & 0xff) as i32,
+        green: ((argb >> 8) & 0xff) as i32,
+        blue: (argb & 0xff) as i32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::{ActionQueue, Index},
+        crust::{action, Animation as AnimationPart, Vector, VectorAnimation},
+        resources::testing::MemorySource,
+    };
+    use std::sync::{mpsc, Arc};
+
+    fn map() -> TileMap {
+        serde_json::from_str(
+            r#"{
+                "width": 4, "height": 4, "tilewidth": 16, "tileheight": 16,
+                "orientation": "orthogonal", "infinite": false,
+                "tilesets": [], "layers": []
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn object(properties: Vec<ObjectProperty>) -> Object {
+        Object {
+            class: "chest".to_owned(),
+            id: 1,
+            name: "chest".to_owned(),
+            x: 16,
+            y: 32,
+            width: 16,
+            height: 8,
+            gid: None,
+            properties,
+        }
+    }
+
+    fn components(
+        properties: Vec<ObjectProperty>,
+        source: &dyn AssetSource,
+    ) -> Result<ObjectComponents, Status> {
+        let map = map();
+        let context = MapContext {
+            resource: "maps/level",
+            map: &map,
+            grid: map.grid(),
+            source,
+            object_names: HashMap::from([(2, "door")]),
+        };
+        ObjectComponents::new(&object(properties), &context, &World::new())
+    }
+
+    #[test]
+    fn properties_map_to_components() {
+        let script = AnimationScript {
+            id: "shake".to_owned(),
+            animation: vec![AnimationPart {
+                translation: Some(VectorAnimation {
+                    vec: Some(Vector::default()),
+                    delay: 10,
+                    repeat: 1,
+                }),
+                ..Default::default()
+            }],
+            repeat: 1,
+        };
+        let source = MemorySource::default().with("maps/shake.pb", script.encode_to_vec());
+
+        let components = components(
+            vec![
+                ObjectProperty::IntType {
+                    name: "z".to_owned(),
+                    value: 3,
+                },
+                ObjectProperty::BoolType {
+                    name: "rigid_body".to_owned(),
+                    value: true,
+                },
+                ObjectProperty::FileType {
+                    name: "script".to_owned(),
+                    value: "shake.pb".to_owned(),
+                },
+                ObjectProperty::StringType {
+                    name: "on_collision".to_owned(),
+                    value: "hero, arrow,".to_owned(),
+                },
+            ],
+            &source,
+        )
+        .unwrap();
+
+        assert_eq!(components.position, Rect::new(16, 32, 16, 8));
+        assert_eq!(components.depth, 3);
+        assert!(components.rigid_body);
+        assert!(components.sprite.is_none());
+        assert_eq!(components.animation.unwrap().runner.script, script);
+        let others: Vec<_> = components
+            .collisions
+            .unwrap()
+            .on_collision
+            .into_iter()
+            .map(|action| (action.scene_node_id, action.other_id))
+            .collect();
+        assert_eq!(
+            others,
+            vec![
+                ("chest".to_owned(), "hero".to_owned()),
+                ("chest".to_owned(), "arrow".to_owned())
+            ]
+        );
+        assert!(components.properties.0.is_empty());
+    }
+
+    #[test]
+    fn other_properties_keep_their_types() {
+        let components = components(
+            vec![
+                ObjectProperty::ObjectType {
+                    name: "target".to_owned(),
+                    value: 2,
+                },
+                ObjectProperty::ClassType {
+                    name: "loot".to_owned(),
+                    propertytype: "Loot".to_owned(),
+                    value: vec![
+                        ObjectProperty::ColorType {
+                            name: "glow".to_owned(),
+                            value: "#00ff00".to_owned(),
+                        },
+                        ObjectProperty::FloatType {
+                            name: "weight".to_owned(),
+                            value: 2.0,
+                        },
+                        ObjectProperty::ObjectType {
+                            name: "key".to_owned(),
+                            value: 2,
+                        },
+                    ],
+                },
+            ],
+            &MemorySource::default(),
+        )
+        .unwrap();
+
+        let properties = &components.properties.0;
+        assert_eq!(
+            properties["target"].value,
+            Some(property_value::Value::ObjectValue("door".to_owned()))
+        );
+        let loot = match &properties["loot"].value {
+            Some(property_value::Value::ClassValue(loot)) => loot,
+            value => panic!("unexpected value {value:?}"),
+        };
+        assert_eq!(loot.r#type, "Loot");
+        assert_eq!(
+            loot.members["glow"].value,
+            Some(property_value::Value::ColorValue(RgBa {
+                red: 0,
+                green: 255,
+                blue: 0,
+                alpha: 255
+            }))
+        );
+        assert_eq!(
+            loot.members["weight"].value,
+            Some(property_value::Value::FloatValue(2.0))
+        );
+        assert_eq!(
+            loot.members["key"].value,
+            Some(property_value::Value::ObjectValue("door".to_owned()))
+        );
+    }
+
+    #[test]
+    fn reserved_properties_need_their_type() {
+        let result = components(
+            vec![ObjectProperty::StringType {
+                name: "z".to_owned(),
+                value: "3".to_owned(),
+            }],
+            &MemorySource::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn tile_objects_are_drawn_with_their_tile_and_announced() {
+        let source = Arc::new(MemorySource::default().with(
+            "tilesets/terrain.tsx",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tileset name="terrain" tilewidth="16" tileheight="16" tilecount="2" columns="2">
+              <image source="../images/terrain.png" width="32" height="16"/>
+            </tileset>"#,
+        ));
+        let mut world = World::new();
+        world.register::<Id>();
+        world.register::<Position>();
+        world.register::<Depth>();
+        world.register::<Velocity>();
+        world.register::<Rotation>();
+        world.register::<Scaling>();
+        world.register::<Properties>();
+        world.register::<SpriteInfo>();
+        world.insert(SpriteManager::create(source.clone()));
+        let (tx, rx) = mpsc::channel();
+        ACTION_QUEUE.with(|queue| *queue.borrow_mut() = Some(ActionQueue::new(tx)));
+        INDEX.with(|index| *index.borrow_mut() = Some(Index::new()));
+
+        let map: TileMap = serde_json::from_str(
+            r#"{
+                "width": 4, "height": 4, "tilewidth": 16, "tileheight": 16,
+                "orientation": "orthogonal", "infinite": false,
+                "tilesets": [{"firstgid": 1, "source": "tilesets/terrain.tsx"}],
+                "layers": []
+            }"#,
+        )
+        .unwrap();
+        let context = MapContext {
+            resource: "maps/level",
+            map: &map,
+            grid: map.grid(),
+            source: source.as_ref(),
+            object_names: HashMap::new(),
+        };
+        let object = Object {
+            width: 0,
+            height: 0,
+            gid: Some(2),
+            ..object(vec![])
+        };
+        let components = ObjectComponents::new(&object, &context, &world).unwrap();
+
+        // Tile objects hang above their anchor at (16, 32).
+        assert_eq!(components.position, Rect::new(16, 16, 16, 16));
+        let sprite = components.sprite.as_ref().unwrap();
+        assert_eq!(sprite.sprite_id, "tilesets/terrain");
+        assert_eq!(sprite.texture_id, "images/terrain");
+        assert_eq!(sprite.frame_index, 1);
+        assert_eq!(sprite.bounding_box, Rect::new(16, 0, 16, 16));
+
+        let entity = components.build(&mut world);
+        let mut entity_id = None;
+        INDEX.with(|index| {
+            if let Some(index) = &*index.borrow() {
+                entity_id = index.find_entity("chest");
+            }
+        });
+        assert_eq!(entity_id, Some(entity.id()));
+        let event = match rx.try_recv().unwrap().action {
+            Some(action::Action::Emit(emit)) => emit.event.unwrap(),
+            action => panic!("unexpected action {action:?}"),
+        };
+        assert_eq!(event.event_id, "chest");
+        assert_eq!(
+            event.event,
+            Some(event::Event::SceneNodeCreated(SceneNodeEvent {
+                scene_node_id: "chest".to_owned(),
+                class: "chest".to_owned(),
+                position: Some(Box {
+                    left: 16,
+                    top: 16,
+                    width: 16,
+                    height: 16,
+                }),
+            }))
+        );
+    }
+
+    #[test]
+    fn colours_are_parsed_with_optional_alpha() {
+        assert_eq!(
+            parse_color("#80ff0010").unwrap(),
+            RgBa {
+                red: 255,
+                green: 0,
+                blue: 16,
+                alpha: 128
+            }
+        );
+        assert_eq!(parse_color("#00ff00").unwrap().alpha, 255);
+        assert!(parse_color("00ff00").is_err());
+    }
+}
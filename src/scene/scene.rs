@@ -1,16 +1,118 @@
-use sdl2::rect::Rect;
+use crate::resources::{TileFlip, TileGrid};
+use sdl2::rect::{Point, Rect};
+use specs::Entity;
 
 pub struct Scene {
     pub layers: Vec<SceneLayer>,
     pub bounds: Rect,
+    pub tile_size: (u32, u32),
+    pub grid: TileGrid,
+
+    // Entities created from the map's object layers.
+    pub objects: Vec<Entity>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene {
+            layers: vec![],
+            bounds: Rect::new(0, 0, 0, 0),
+            tile_size: (0, 0),
+            grid: TileGrid::default(),
+            objects: vec![],
+        }
+    }
 }
-#[derive(Default)]
+
 pub struct SceneLayer {
+    pub name: String,
     pub tiles: Vec<TileInfo>,
+
+    pub visible: bool,
+    pub opacity: f64,
+
+    // Fixed pixel offset of the layer in the world.
+    pub offset: Point,
+
+    // Scrolling factor of the layer relative to the viewport. Layers with
+    // factors below 1.0 scroll slower than the viewport giving the impression
+    // of depth, while a factor of 0.0 keeps the layer fixed on screen.
+    pub parallax: (f64, f64),
+}
+
+impl Default for SceneLayer {
+    fn default() -> Self {
+        SceneLayer {
+            name: String::default(),
+            tiles: vec![],
+            visible: true,
+            opacity: 1.0,
+            offset: Point::new(0, 0),
+            parallax: (1.0, 1.0),
+        }
+    }
+}
+
+impl SceneLayer {
+    /// Returns the translation to apply on the layer's tiles in order to move
+    /// them from world to screen coordinates given the current viewport.
+    pub fn screen_offset(&self, viewport: Rect) -> Point {
+        Point::new(
+            self.offset.x() - (viewport.x() as f64 * self.parallax.0) as i32,
+            self.offset.y() - (viewport.y() as f64 * self.parallax.1) as i32,
+        )
+    }
+
+    pub fn alpha(&self) -> u8 {
+        (self.opacity.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
 }
 
 pub struct TileInfo {
     pub texture_id: String,
     pub texture_position: Rect,
     pub canvas_position: Rect,
+    pub flip: TileFlip,
+
+    // Index of the tile's animation in TileAnimations, if it is animated.
+    pub animation: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_offset_follows_viewport() {
+        let layer = SceneLayer::default();
+        assert_eq!(
+            layer.screen_offset(Rect::new(100, 40, 320, 240)),
+            Point::new(-100, -40)
+        );
+    }
+
+    #[test]
+    fn screen_offset_with_parallax() {
+        let layer = SceneLayer {
+            offset: Point::new(8, 0),
+            parallax: (0.5, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            layer.screen_offset(Rect::new(100, 40, 320, 240)),
+            Point::new(-42, 0)
+        );
+    }
+
+    #[test]
+    fn alpha_from_opacity() {
+        let mut layer = SceneLayer::default();
+        assert_eq!(layer.alpha(), 255);
+
+        layer.opacity = 0.5;
+        assert_eq!(layer.alpha(), 128);
+
+        layer.opacity = 1.7;
+        assert_eq!(layer.alpha(), 255);
+    }
 }
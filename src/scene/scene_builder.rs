@@ -1,109 +1,266 @@
-use super::scene::{Scene, SceneLayer, TileInfo};
+use super::{
+    object_properties::{MapContext, ObjectComponents},
+    scene::{Scene, SceneLayer, TileInfo},
+    scene_manager::delete_objects,
+    tile_animation::{TileAnimation, TileAnimations},
+};
 use crate::{
-    components::{Id, Position, RigidBody},
-    resources::{ObjectProperty, SpriteManager, TileMap},
+    core::Status,
+    resources::{AssetSource, SpriteManager, TileFlip, TileMap},
 };
-use sdl2::rect::Rect;
+use sdl2::rect::{Point, Rect};
 use specs::prelude::*;
+use std::{collections::HashMap, time::Duration};
 
 pub struct SceneBuilder;
 
 impl SceneBuilder {
-    pub fn build(map: &TileMap, sprite_manager: &SpriteManager, world: &mut World) -> Scene {
-        let ranges = Self::build_tileset_ranges(map);
+    /// Builds the scene of `map`, whose tilesets must be loaded in the
+    /// world's SpriteManager. The `previous_objects` of the scene it replaces
+    /// are deleted once the map is known to be valid, before the new objects
+    /// take over their names.
+    pub fn build(
+        resource: &str,
+        map: &TileMap,
+        previous_objects: &[Entity],
+        source: &dyn AssetSource,
+        world: &mut World,
+    ) -> Result<Scene, Status> {
+        // Tiles of the same type share one animation.
+        let mut animations = vec![];
+        let mut animation_index = HashMap::new();
 
+        let grid = map.grid();
         let mut layers = vec![];
+        let mut object_layers = vec![];
+        let sprite_manager = world.read_resource::<SpriteManager>();
         for layer in &map.layers {
             match layer.layer_type.as_str() {
                 "tilelayer" => {
-                    layers.push(SceneLayer {
-                        tiles: layer
-                            .data
-                            .iter()
-                            .enumerate()
-                            .filter(|(_, tile_id)| **tile_id > 0)
-                            .map(|(i, tile_id)| {
-                                let range = ranges
-                                    .iter()
-                                    .find(|range| range.first <= *tile_id && *tile_id < range.last)
-                                    .expect(&format!(
-                                        "Failed to find {tile_id} in ranges: {:?}",
-                                        &ranges
-                                    ));
-                                TileInfo {
-                                    texture_id: range.resource.clone(),
-                                    texture_position: sprite_manager
-                                        .get_box(&range.resource, (tile_id - range.first) as usize)
-                                        .expect(&format!(
-                                            "Tile index '{tile_id}' exceeds available tiles in {}",
-                                            &range.resource
-                                        )),
-                                    canvas_position: Rect::new(
-                                        ((i as u32 % map.width) * map.tilewidth) as i32,
-                                        ((i as u32 / map.width) * map.tileheight) as i32,
-                                        map.tilewidth,
-                                        map.tileheight,
-                                    ),
-                                }
-                            })
-                            .collect(),
-                    });
-                }
-                "objectgroup" => {
-                    for object in &layer.objects {
-                        let mut builder =
-                            world
-                                .create_entity()
-                                .with(Id(object.name.clone()))
-                                .with(Position(Rect::new(
-                                    object.x,
-                                    object.y,
-                                    object.width,
-                                    object.height,
-                                )));
-
-                        for property in &object.properties {
-                            if let ObjectProperty::BoolType { name, value } = property {
-                                if name == "rigid_body" && *value {
-                                    builder = builder.with(RigidBody {});
-                                }
+                    let mut tiles = vec![];
+                    for (x, y, tile_id) in layer.tiles() {
+                        let (tileset, tile_index) = map.tileset(tile_id).ok_or_else(|| {
+                            Status::invalid_argument(format!(
+                                "Tile {tile_id} at ({x}, {y}) of layer '{}' is not in any tileset",
+                                layer.name
+                            ))
+                        })?;
+                        let resource = tileset.resource()?;
+                        // Tilesets read from .tsx files name the texture
+                        // after their image rather than the tileset.
+                        let texture_id = match sprite_manager.get(resource) {
+                            Some(sprite) => &sprite.texture_id,
+                            None => {
+                                return Err(Status::not_found(format!(
+                                    "Tileset '{resource}' is not loaded"
+                                )))
                             }
-                        }
-                        builder.build();
+                        };
+
+                        let key = (resource.to_owned(), tile_index);
+                        let animation = match animation_index.get(&key) {
+                            Some(animation) => *animation,
+                            None => {
+                                let animation = Self::build_tile_animation(
+                                    &sprite_manager,
+                                    resource,
+                                    tile_index,
+                                )?
+                                .map(|animation| {
+                                    animations.push(animation);
+                                    animations.len() - 1
+                                });
+                                animation_index.insert(key, animation);
+                                animation
+                            }
+                        };
+
+                        let texture_position = sprite_manager
+                            .get_box(resource, tile_index as usize)
+                            .ok_or_else(|| {
+                                Status::invalid_argument(format!(
+                                    "Tile {tile_id} at ({x}, {y}) of layer '{}' exceeds available tiles in '{resource}'",
+                                    layer.name
+                                ))
+                            })?;
+                        // Tiled aligns tiles with the bottom left corner of
+                        // their cell, so larger tiles stick out at the top.
+                        let cell = grid.tile_rect(x, y);
+                        tiles.push(TileInfo {
+                            texture_id: texture_id.clone(),
+                            texture_position,
+                            canvas_position: Rect::new(
+                                cell.left(),
+                                cell.bottom() - texture_position.height() as i32,
+                                texture_position.width(),
+                                texture_position.height(),
+                            ),
+                            flip: TileFlip::from_gid(tile_id),
+                            animation,
+                        });
                     }
+
+                    // Tiles that overlap their neighbours, as in isometric
+                    // maps, are drawn from back to front.
+                    tiles.sort_by_key(|tile| {
+                        (tile.canvas_position.bottom(), tile.canvas_position.left())
+                    });
+
+                    // Layers are offset by whole tiles, which shift along the
+                    // tile axes of the map's orientation.
+                    let (shifted, origin) =
+                        (grid.tile_rect(layer.x, layer.y), grid.tile_rect(0, 0));
+                    layers.push(SceneLayer {
+                        name: layer.name.clone(),
+                        visible: layer.visible,
+                        opacity: layer.opacity,
+                        offset: Point::new(
+                            shifted.x() - origin.x() + layer.offsetx as i32,
+                            shifted.y() - origin.y() + layer.offsety as i32,
+                        ),
+                        parallax: (layer.parallaxx, layer.parallaxy),
+                        tiles,
+                    });
                 }
+                "objectgroup" => object_layers.push(layer),
                 _ => {}
             };
         }
+        // Objects with tile sprites load them into the SpriteManager.
+        drop(sprite_manager);
+
+        // Objects are only created once the map is known to be valid, so that
+        // a failed build leaves no entities behind.
+        let context = MapContext {
+            resource,
+            map,
+            grid: grid.clone(),
+            source,
+            object_names: object_layers
+                .iter()
+                .flat_map(|layer| &layer.objects)
+                .map(|object| (object.id, object.name.as_str()))
+                .collect(),
+        };
+        let components = object_layers
+            .iter()
+            .flat_map(|layer| &layer.objects)
+            .map(|object| ObjectComponents::new(object, &context, world))
+            .collect::<Result<Vec<_>, Status>>()?;
+        delete_objects(previous_objects, world);
+        let objects = components
+            .into_iter()
+            .map(|components| components.build(world))
+            .collect();
 
-        Scene {
+        world.insert(TileAnimations(animations));
+
+        Ok(Scene {
             layers,
-            bounds: Rect::new(0, 0, map.width * map.tilewidth, map.height * map.tileheight),
-        }
+            bounds: map.bounds(),
+            tile_size: (map.tilewidth, map.tileheight),
+            grid,
+            objects,
+        })
     }
 
-    fn build_tileset_ranges(map: &TileMap) -> Vec<Range> {
-        let tilesets = &map.tilesets;
-
-        let mut ranges = vec![];
-        for i in 0..tilesets.len() {
-            ranges.push(Range {
-                first: tilesets[i].firstgid,
-                last: match i + 1 < tilesets.len() {
-                    true => tilesets[i + 1].firstgid - 1,
-                    false => u32::MAX,
-                },
-                resource: tilesets[i].source.strip_suffix(".tsx").unwrap().to_owned(),
-            });
-        }
+    fn build_tile_animation(
+        sprite_manager: &SpriteManager,
+        resource: &str,
+        tile_index: u32,
+    ) -> Result<Option<TileAnimation>, Status> {
+        let tile = match sprite_manager.get(resource).and_then(|sprite| {
+            sprite
+                .tiles
+                .iter()
+                .find(|tile| tile.id == tile_index && !tile.animation.is_empty())
+        }) {
+            Some(tile) => tile,
+            None => return Ok(None),
+        };
 
-        ranges
+        let mut frames = vec![];
+        for frame in &tile.animation {
+            let texture_position = sprite_manager
+                .get_box(resource, frame.tileid as usize)
+                .ok_or_else(|| {
+                    Status::invalid_argument(format!(
+                        "Animation frame {} of tile {tile_index} exceeds available tiles in '{resource}'",
+                        frame.tileid
+                    ))
+                })?;
+            frames.push((
+                texture_position,
+                Duration::from_millis(frame.duration as u64),
+            ));
+        }
+        Ok(Some(TileAnimation::new(frames)))
     }
 }
 
-#[derive(Debug)]
-struct Range {
-    first: u32,
-    last: u32,
-    resource: String,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{read_tmx, testing::MemorySource};
+    use std::sync::Arc;
+
+    #[test]
+    fn tmx_tiles_use_the_tileset_image() {
+        let source = Arc::new(
+            MemorySource::default()
+                .with(
+                    "maps/level1.tmx",
+                    r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <map orientation="orthogonal" width="2" height="1" tilewidth="16" tileheight="16" infinite="0">
+                      <tileset firstgid="1" source="../tilesets/terrain.tsx"/>
+                      <layer id="1" name="ground" width="2" height="1">
+                        <data encoding="csv">2,1</data>
+                      </layer>
+                    </map>"#,
+                )
+                .with(
+                    "tilesets/terrain.tsx",
+                    r#"<?xml version="1.0" encoding="UTF-8"?>
+                    <tileset name="terrain" tilewidth="16" tileheight="16" tilecount="2" columns="2">
+                      <image source="../images/terrain.png" width="32" height="16"/>
+                    </tileset>"#,
+                ),
+        );
+        let map = read_tmx(source.as_ref(), "maps/level1").unwrap();
+        let mut sprite_manager = SpriteManager::create(source.clone());
+        sprite_manager.load("tilesets/terrain").unwrap();
+        let mut world = World::new();
+        world.insert(sprite_manager);
+
+        let scene =
+            SceneBuilder::build("maps/level1", &map, &[], source.as_ref(), &mut world).unwrap();
+
+        let tiles = &scene.layers[0].tiles;
+        assert_eq!(tiles[0].texture_id, "images/terrain");
+        assert_eq!(tiles[0].texture_position, Rect::new(16, 0, 16, 16));
+        assert_eq!(tiles[1].canvas_position, Rect::new(16, 0, 16, 16));
+    }
+
+    #[test]
+    fn layer_offsets_follow_the_orientation() {
+        let map: TileMap = serde_json::from_str(
+            r#"{
+                "width": 4, "height": 4, "tilewidth": 64, "tileheight": 32,
+                "orientation": "isometric", "infinite": false, "tilesets": [],
+                "layers": [{
+                    "type": "tilelayer", "id": 1, "name": "ground", "opacity": 1.0,
+                    "visible": true, "x": 1, "y": 0, "offsety": 4.0
+                }]
+            }"#,
+        )
+        .unwrap();
+        let source = Arc::new(MemorySource::default());
+        let mut world = World::new();
+        world.insert(SpriteManager::create(source.clone()));
+
+        let scene =
+            SceneBuilder::build("maps/level1", &map, &[], source.as_ref(), &mut world).unwrap();
+
+        assert_eq!(scene.layers[0].offset, Point::new(32, 20));
+    }
 }
@@ -1,74 +1,348 @@
-use super::{scene::Scene, scene_builder::SceneBuilder};
+use super::{
+    scene::{Scene, SceneLayer},
+    scene_builder::SceneBuilder,
+    tile_animation::TileAnimations,
+};
 use crate::{
+    action::INDEX,
+    components::Id,
     core::Status,
-    resources::TextureManager,
-    resources::{SpriteManager, TileMapManager},
+    crust::{AssetType, CacheStats},
+    resources::{set_texture_alpha, TextureManager, TileGrid},
+    resources::{
+        AssetSource, ResourceRef, Sprite, SpriteManager, TileMap, TileMapManager, WorldSize,
+    },
 };
+use log::{info, warn};
 use sdl2::{rect::Rect, render::WindowCanvas};
 use specs::prelude::*;
+use std::sync::Arc;
 
 pub struct SceneManager {
     scene: Scene,
+    // Tile map resource of the current scene.
+    resource: String,
     tilemap_manager: TileMapManager,
-    tile_sprite_manager: SpriteManager,
+    source: Arc<dyn AssetSource>,
+
+    // Keep the tile map and tilesets of the current scene loaded.
+    scene_refs: Vec<ResourceRef>,
 }
 
 impl SceneManager {
-    pub fn new(resource_path: &str) -> Self {
+    pub fn new(source: Arc<dyn AssetSource>) -> Self {
         SceneManager {
-            scene: Scene {
-                layers: vec![],
-                bounds: Rect::new(0, 0, 0, 0),
-            },
-            tilemap_manager: TileMapManager::create(resource_path),
-            tile_sprite_manager: SpriteManager::create(resource_path),
+            scene: Scene::default(),
+            resource: String::default(),
+            tilemap_manager: TileMapManager::create(Arc::clone(&source)),
+            source,
+            scene_refs: vec![],
+        }
+    }
+
+    pub fn set_budget(&mut self, budget: u64) {
+        self.tilemap_manager.set_budget(budget);
+    }
+
+    /// Caches a tile map and its tilesets that were loaded elsewhere, e.g.
+    /// preloaded in the background. Tilesets are cached with the sprite
+    /// sheets, as objects of the map may use their tiles as sprites.
+    pub fn insert_tile_map(
+        &mut self,
+        resource: &str,
+        map: TileMap,
+        tilesets: Vec<(String, Sprite)>,
+        world: &World,
+    ) {
+        let mut sprite_manager = world.write_resource::<SpriteManager>();
+        for (tileset, sprite) in tilesets {
+            sprite_manager.insert(tileset.as_str(), sprite);
         }
+        self.tilemap_manager.insert(resource, map);
+    }
+
+    /// Returns the cache stats of tile maps.
+    pub fn stats(&self) -> CacheStats {
+        self.tilemap_manager.stats()
     }
 
     pub fn scene_bounds(&self) -> Rect {
         self.scene.bounds
     }
 
+    pub fn tile_size(&self) -> (u32, u32) {
+        self.scene.tile_size
+    }
+
+    /// Returns the layout of the current scene's tiles, which converts between
+    /// tile and world coordinates, or None if no scene is loaded.
+    pub fn grid(&self) -> Option<&TileGrid> {
+        match self.resource.is_empty() {
+            true => None,
+            false => Some(&self.scene.grid),
+        }
+    }
+
     pub fn load(&mut self, resource: &str, world: &mut World) -> Result<(), Status> {
+        // Resources are held as soon as they are loaded, so that loading the
+        // following ones cannot evict them.
         self.tilemap_manager.load(resource)?;
+        let mut scene_refs: Vec<ResourceRef> =
+            self.tilemap_manager.acquire(resource).into_iter().collect();
         let map = self.tilemap_manager.get(resource).unwrap();
+        {
+            let mut sprite_manager = world.write_resource::<SpriteManager>();
+            for set in &map.tilesets {
+                let tileset = set.resource()?;
+                sprite_manager.load(tileset)?;
+                scene_refs.extend(sprite_manager.acquire(tileset));
+            }
+        }
+
+        let scene = SceneBuilder::build(
+            resource,
+            map,
+            &self.scene.objects,
+            self.source.as_ref(),
+            world,
+        )
+        .map_err(|e| {
+            Status::invalid_argument(format!("Invalid tile map '{resource}': {}", e.message()))
+        })?;
+        self.scene = scene;
+        self.resource = resource.to_owned();
+        self.scene_refs = scene_refs;
+        info!("scene '{resource}' loaded");
+
+        Ok(())
+    }
+
+    /// Reloads the tile map or sprite sheet `resource`, which may be a
+    /// tileset, if it was loaded before and rebuilds the current scene if it
+    /// uses it. Returns the types of the reloaded assets.
+    pub fn reload(&mut self, resource: &str, world: &mut World) -> Result<Vec<AssetType>, Status> {
+        let mut reloaded = vec![];
+        if self.tilemap_manager.reload(resource)? {
+            reloaded.push(AssetType::TileMap);
+        }
+        if world.write_resource::<SpriteManager>().reload(resource)? {
+            reloaded.push(AssetType::Sprite);
+        }
+
+        if !reloaded.is_empty() && self.uses(resource) {
+            // Objects of the previous build are replaced by the rebuilt ones.
+            let scene_resource = self.resource.clone();
+            self.load(&scene_resource, world)?;
+            *world.write_resource() = WorldSize(self.scene.bounds);
+        }
+
+        Ok(reloaded)
+    }
+
+    /// Unloads the current scene together with its tile map and tilesets that
+    /// are no longer used. Returns the textures of the unloaded tilesets.
+    pub fn unload(&mut self, world: &mut World) -> Result<Vec<String>, Status> {
+        let scene = std::mem::take(&mut self.scene);
+        delete_objects(&scene.objects, world);
+        world.insert(TileAnimations::default());
+        *world.write_resource() = WorldSize(Rect::new(0, 0, 0, 0));
 
-        for set in &map.tilesets {
-            self.tile_sprite_manager.load(
-                &set.source
-                    .strip_suffix(".tsx")
-                    .expect("TileSet does not have the expected format."),
-            )?;
+        self.scene_refs.clear();
+        let resource = std::mem::take(&mut self.resource);
+        let tilesets: Vec<String> = match self.tilemap_manager.get(&resource) {
+            Some(map) => map
+                .tilesets
+                .iter()
+                .filter_map(|set| set.source.strip_suffix(".tsx"))
+                .map(|tileset| tileset.to_owned())
+                .collect(),
+            None => vec![],
+        };
+        self.tilemap_manager.unload(&resource)?;
+
+        let mut sprite_manager = world.write_resource::<SpriteManager>();
+        let mut textures = vec![];
+        for tileset in tilesets {
+            let texture_id = match sprite_manager.get(&tileset) {
+                Some(sprite) => sprite.texture_id.clone(),
+                None => continue,
+            };
+            // Tilesets that are still used as sprites by scene nodes stay.
+            if let Ok(true) = sprite_manager.unload(&tileset) {
+                textures.push(texture_id);
+            }
         }
+        Ok(textures)
+    }
+
+    fn uses(&self, resource: &str) -> bool {
+        if self.resource.is_empty() {
+            return false;
+        }
+        self.resource == resource
+            || self.tilemap_manager.get(&self.resource).is_some_and(|map| {
+                map.tilesets
+                    .iter()
+                    .any(|set| set.source.strip_suffix(".tsx") == Some(resource))
+            })
+    }
+
+    pub fn set_layer_visibility(&mut self, layer_id: &str, visible: bool) -> Result<(), Status> {
+        self.find_layer(layer_id)?.visible = visible;
+        Ok(())
+    }
 
-        self.scene = SceneBuilder::build(map, &self.tile_sprite_manager, world);
-        println!("🦀 scene '{resource}' loaded");
+    pub fn set_layer_opacity(&mut self, layer_id: &str, opacity: f64) -> Result<(), Status> {
+        if !(0.0..=1.0).contains(&opacity) {
+            return Err(Status::invalid_argument(format!(
+                "Layer opacity should be in [0, 1] range but was {opacity}"
+            )));
+        }
 
+        self.find_layer(layer_id)?.opacity = opacity;
         Ok(())
     }
 
+    fn find_layer(&mut self, layer_id: &str) -> Result<&mut SceneLayer, Status> {
+        self.scene
+            .layers
+            .iter_mut()
+            .find(|layer| layer.name == layer_id)
+            .ok_or_else(|| Status::not_found(format!("Scene layer '{layer_id}' not found")))
+    }
+
     pub fn render(
         &self,
         viewport: Rect,
         canvas: &mut WindowCanvas,
         texture_manager: &mut TextureManager<sdl2::video::WindowContext>,
+        tile_animations: &TileAnimations,
     ) -> Result<(), Status> {
         for layer in &self.scene.layers {
+            if !layer.visible || layer.opacity <= 0.0 {
+                continue;
+            }
+
+            let offset = layer.screen_offset(viewport);
+            let alpha = layer.alpha();
+
             for tile in &layer.tiles {
-                let texture = texture_manager.load(&tile.texture_id).unwrap();
-                canvas.copy(
+                let texture = texture_manager.load(&tile.texture_id)?;
+                set_texture_alpha(&texture, alpha);
+                let texture_position = match tile.animation {
+                    Some(index) => tile_animations.0[index].texture_position(),
+                    None => tile.texture_position,
+                };
+                let (angle, flip_horizontal, flip_vertical) = tile.flip.transform();
+                canvas.copy_ex(
                     &texture,
-                    tile.texture_position,
+                    texture_position,
                     Rect::new(
-                        tile.canvas_position.x() - viewport.x(),
-                        tile.canvas_position.y() - viewport.y(),
+                        tile.canvas_position.x() + offset.x(),
+                        tile.canvas_position.y() + offset.y(),
                         tile.canvas_position.width(),
                         tile.canvas_position.height(),
                     ),
+                    angle,
+                    None,
+                    flip_horizontal,
+                    flip_vertical,
                 )?;
+                // Textures are shared with other layers and sprites.
+                if alpha < u8::MAX {
+                    set_texture_alpha(&texture, u8::MAX);
+                }
             }
         }
 
         Ok(())
     }
 }
+
+/// Deletes the scene nodes created from the objects of a map and removes them
+/// from the index.
+pub fn delete_objects(objects: &[Entity], world: &mut World) {
+    {
+        let ids = world.read_storage::<Id>();
+        INDEX.with(|index| {
+            if let Some(index) = &mut *index.borrow_mut() {
+                for (entity, id) in objects.iter().filter_map(|e| Some((e, ids.get(*e)?))) {
+                    if index.find_entity(&id.0) == Some(entity.id()) {
+                        index.remove_entity(&id.0);
+                    }
+                }
+            }
+        });
+    }
+    if let Err(e) = world.delete_entities(objects) {
+        warn!("Failed to remove scene objects: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::Index,
+        components::{Depth, Position, Properties, Rotation, Scaling, Velocity},
+        resources::testing::MemorySource,
+    };
+
+    fn map_with_player(x: i32) -> String {
+        format!(
+            r#"{{
+                "width": 4, "height": 4, "tilewidth": 16, "tileheight": 16,
+                "orientation": "orthogonal", "infinite": false, "tilesets": [],
+                "layers": [{{
+                    "type": "objectgroup", "id": 1, "name": "objects", "opacity": 1.0,
+                    "visible": true, "x": 0, "y": 0,
+                    "objects": [{{
+                        "class": "spawn", "id": 1, "name": "player",
+                        "x": {x}, "y": 0, "width": 16, "height": 16
+                    }}]
+                }}]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn objects_keep_their_names_across_scenes() {
+        let source = Arc::new(
+            MemorySource::default()
+                .with("maps/level1.json", map_with_player(0))
+                .with("maps/level2.json", map_with_player(32)),
+        );
+        let mut world = World::new();
+        world.register::<Id>();
+        world.register::<Position>();
+        world.register::<Depth>();
+        world.register::<Velocity>();
+        world.register::<Rotation>();
+        world.register::<Scaling>();
+        world.register::<Properties>();
+        world.insert(SpriteManager::create(source.clone()));
+        INDEX.with(|index| *index.borrow_mut() = Some(Index::new()));
+        let player = || {
+            let mut entity_id = None;
+            INDEX.with(|index| {
+                if let Some(index) = &*index.borrow() {
+                    entity_id = index.find_entity("player");
+                }
+            });
+            entity_id
+        };
+
+        let mut scene_manager = SceneManager::new(source);
+        scene_manager.load("maps/level1", &mut world).unwrap();
+        assert!(player().is_some());
+
+        scene_manager.load("maps/level2", &mut world).unwrap();
+        world.maintain();
+        let entity = world.entities().entity(player().unwrap());
+        assert!(world.is_alive(entity));
+        assert_eq!(
+            world.read_storage::<Position>().get(entity).unwrap().0,
+            Rect::new(32, 0, 16, 16)
+        );
+    }
+}
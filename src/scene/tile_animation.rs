@@ -0,0 +1,104 @@
+use sdl2::rect::Rect;
+use std::time::Duration;
+
+/// Animations of the tiles in the current scene. There is one animation per
+/// animated tile type that is shared by all tiles of that type, so they are
+/// advanced once per frame regardless of how many tiles use them.
+#[derive(Default)]
+pub struct TileAnimations(pub Vec<TileAnimation>);
+
+pub struct TileAnimation {
+    // Tileset position and duration of each frame.
+    frames: Vec<(Rect, Duration)>,
+    cycle: Duration,
+
+    current: usize,
+    // Time since the start of the current animation cycle.
+    elapsed_time: Duration,
+}
+
+impl TileAnimation {
+    pub fn new(frames: Vec<(Rect, Duration)>) -> Self {
+        TileAnimation {
+            cycle: frames.iter().map(|(_, duration)| *duration).sum(),
+            frames,
+            current: 0,
+            elapsed_time: Duration::ZERO,
+        }
+    }
+
+    pub fn update(&mut self, time_since_last_frame: Duration) {
+        if self.cycle.is_zero() {
+            return;
+        }
+
+        self.elapsed_time = Duration::from_nanos(
+            ((self.elapsed_time + time_since_last_frame).as_nanos() % self.cycle.as_nanos()) as u64,
+        );
+
+        let mut remaining = self.elapsed_time;
+        self.current = 0;
+        while remaining >= self.frames[self.current].1 {
+            remaining -= self.frames[self.current].1;
+            self.current += 1;
+        }
+    }
+
+    /// Returns the position of the current frame in the tileset.
+    pub fn texture_position(&self) -> Rect {
+        self.frames[self.current].0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn water() -> TileAnimation {
+        TileAnimation::new(vec![
+            (Rect::new(0, 0, 16, 16), Duration::from_millis(100)),
+            (Rect::new(16, 0, 16, 16), Duration::from_millis(50)),
+            (Rect::new(32, 0, 16, 16), Duration::from_millis(100)),
+        ])
+    }
+
+    #[test]
+    fn starts_on_first_frame() {
+        let animation = water();
+        assert_eq!(animation.texture_position(), Rect::new(0, 0, 16, 16));
+    }
+
+    #[test]
+    fn advances_with_frame_durations() {
+        let mut animation = water();
+
+        animation.update(Duration::from_millis(99));
+        assert_eq!(animation.texture_position(), Rect::new(0, 0, 16, 16));
+
+        animation.update(Duration::from_millis(1));
+        assert_eq!(animation.texture_position(), Rect::new(16, 0, 16, 16));
+
+        animation.update(Duration::from_millis(60));
+        assert_eq!(animation.texture_position(), Rect::new(32, 0, 16, 16));
+    }
+
+    #[test]
+    fn wraps_around_over_long_frames() {
+        let mut animation = water();
+
+        // Two full cycles of 250ms plus 120ms.
+        animation.update(Duration::from_millis(620));
+        assert_eq!(animation.texture_position(), Rect::new(16, 0, 16, 16));
+    }
+
+    #[test]
+    fn zero_durations_do_not_advance() {
+        let mut animation = TileAnimation::new(vec![
+            (Rect::new(0, 0, 16, 16), Duration::ZERO),
+            (Rect::new(16, 0, 16, 16), Duration::ZERO),
+        ]);
+
+        animation.update(Duration::from_millis(100));
+        assert_eq!(animation.texture_position(), Rect::new(0, 0, 16, 16));
+    }
+}
@@ -2,17 +2,20 @@ use crate::{
     action::ActionQueue,
     animation::Animated,
     components::{
-        Animation, AnimationRunningState, Id, Position, Rotation, Scaling, SpriteInfo, Velocity,
+        Animation, AnimationRunningState, Id, NodeTimeScale, Position, Rotation, Scaling,
+        ScreenSpace, ShapeInfo, SpriteInfo, Velocity,
     },
     crust::{event, AnimationEvent, Vector},
-    resources::SpriteManager,
+    resources::{SpriteManager, UnscaledTime},
 };
+use log::warn;
 use specs::prelude::*;
 use std::time::Duration;
 
 #[derive(SystemData)]
 pub struct AnimatorSystemData<'a> {
     time_since_last_frame: ReadExpect<'a, Duration>,
+    unscaled_time: ReadExpect<'a, UnscaledTime>,
     sprite_manager: WriteExpect<'a, SpriteManager>,
     entities: Entities<'a>,
     updater: Read<'a, LazyUpdate>,
@@ -24,6 +27,9 @@ pub struct AnimatorSystemData<'a> {
     rotation: WriteStorage<'a, Rotation>,
     scaling: WriteStorage<'a, Scaling>,
     sprite_info: WriteStorage<'a, SpriteInfo>,
+    shape_info: WriteStorage<'a, ShapeInfo>,
+    time_scales: ReadStorage<'a, NodeTimeScale>,
+    screen_space: ReadStorage<'a, ScreenSpace>,
 }
 
 pub struct AnimatorSystem {
@@ -36,7 +42,19 @@ impl<'a> System<'a> for AnimatorSystem {
     fn run(&mut self, data: Self::SystemData) {
         let mut data = data;
 
-        for (entity, id, animation, position, velocity, rotation, scaling, sprite_info) in (
+        for (
+            entity,
+            id,
+            animation,
+            position,
+            velocity,
+            rotation,
+            scaling,
+            sprite_info,
+            shape_info,
+            time_scale,
+            screen_space,
+        ) in (
             &data.entities,
             &data.ids,
             &mut data.animations,
@@ -44,11 +62,37 @@ impl<'a> System<'a> for AnimatorSystem {
             &mut data.velocities,
             &mut data.rotation,
             &mut data.scaling,
-            &mut data.sprite_info,
+            (&mut data.sprite_info).maybe(),
+            (&mut data.shape_info).maybe(),
+            (&data.time_scales).maybe(),
+            (&data.screen_space).maybe(),
         )
             .join()
         {
-            let sprite = &data.sprite_manager.load(&sprite_info.texture_id).unwrap();
+            // Screen space nodes are not affected by the global time scale,
+            // so that they keep working while the game is paused.
+            let mut time_since_last_frame = match screen_space {
+                Some(_) => data.unscaled_time.0,
+                None => *data.time_since_last_frame,
+            };
+            if let Some(time_scale) = time_scale {
+                time_since_last_frame = time_since_last_frame.mul_f64(time_scale.0);
+            }
+
+            let sprite = match &sprite_info {
+                // Sprites may have been evicted and fail to load again.
+                Some(sprite_info) => match data.sprite_manager.load(&sprite_info.sprite_id) {
+                    Ok(sprite) => Some(sprite),
+                    Err(e) => {
+                        warn!(
+                            "Failed to animate scene node '{}' with sprite '{}': {e}",
+                            id.0, sprite_info.sprite_id
+                        );
+                        continue;
+                    }
+                },
+                None => None,
+            };
             let mut animated = Animated::new(
                 id,
                 position,
@@ -57,6 +101,7 @@ impl<'a> System<'a> for AnimatorSystem {
                 scaling,
                 sprite_info,
                 sprite,
+                shape_info,
                 Some(&self.queue),
             );
 
@@ -67,11 +112,11 @@ impl<'a> System<'a> for AnimatorSystem {
             if animation.runner.state() == AnimationRunningState::Running {
                 animation
                     .runner
-                    .progress(*data.time_since_last_frame, &mut animated);
+                    .progress(time_since_last_frame, &mut animated);
             }
 
             if animation.runner.state() == AnimationRunningState::Finished {
-                self.emit_done(id, animation, position, sprite_info);
+                self.emit_done(id, animation, animated.position, animated.frame_index());
                 data.updater.remove::<Animation>(entity);
             }
         }
@@ -83,13 +128,7 @@ impl AnimatorSystem {
         AnimatorSystem { queue }
     }
 
-    fn emit_done(
-        &self,
-        id: &Id,
-        script: &Animation,
-        position: &Position,
-        sprite_info: &SpriteInfo,
-    ) {
+    fn emit_done(&self, id: &Id, script: &Animation, position: &Position, frame_index: usize) {
         self.queue.emit(
             format!("{}_script_done", id.0),
             event::Event::AnimationScriptDone(AnimationEvent {
@@ -99,8 +138,120 @@ impl AnimatorSystem {
                     y: position.0.y() as f64,
                     z: 0.0,
                 }),
-                frame_index: sprite_info.frame_index as u32,
+                frame_index: frame_index as u32,
             }),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crust::{Anchor, Animation as AnimationPart, AnimationScript, VectorAnimation},
+        resources::TileFlip,
+    };
+    use sdl2::rect::{Point, Rect};
+    use std::sync::mpsc;
+
+    fn create_world(time_since_last_frame: Duration, unscaled_time: Duration) -> World {
+        let mut world = World::new();
+        world.register::<Id>();
+        world.register::<Animation>();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        world.register::<Rotation>();
+        world.register::<Scaling>();
+        world.register::<SpriteInfo>();
+        world.register::<ShapeInfo>();
+        world.register::<NodeTimeScale>();
+        world.register::<ScreenSpace>();
+        world.insert(time_since_last_frame);
+        world.insert(UnscaledTime(unscaled_time));
+        world.insert(SpriteManager::mock(vec![]));
+        world
+    }
+
+    // Creates a node that speeds up by 1px to the right every 10ms.
+    fn create_node<'a>(world: &'a mut World, id: &str) -> EntityBuilder<'a> {
+        world
+            .create_entity()
+            .with(Id(id.to_owned()))
+            .with(Animation::new(AnimationScript {
+                id: "move_right".to_owned(),
+                animation: vec![AnimationPart {
+                    translation: Some(VectorAnimation {
+                        vec: Some(Vector {
+                            x: 1.0,
+                            ..Default::default()
+                        }),
+                        delay: 10,
+                        repeat: 10,
+                    }),
+                    ..Default::default()
+                }],
+                repeat: 1,
+            }))
+            .with(Position(Rect::new(0, 0, 4, 4)))
+            .with(Velocity::default())
+            .with(Rotation::default())
+            .with(Scaling::default())
+    }
+
+    fn run(world: &mut World) {
+        let (tx, _rx) = mpsc::channel();
+        AnimatorSystem::new(ActionQueue::new(tx)).run_now(world);
+        world.maintain();
+    }
+
+    fn velocity(world: &World, entity: Entity) -> Point {
+        world.read_storage::<Velocity>().get(entity).unwrap().0
+    }
+
+    #[test]
+    fn node_time_scale_multiplies_the_frame_time() {
+        let mut world = create_world(Duration::from_millis(10), Duration::from_millis(10));
+        let normal = create_node(&mut world, "normal").build();
+        let fast = create_node(&mut world, "fast")
+            .with(NodeTimeScale(2.0))
+            .build();
+
+        run(&mut world);
+        assert_eq!(velocity(&world, normal), Point::new(1, 0));
+        assert_eq!(velocity(&world, fast), Point::new(2, 0));
+    }
+
+    #[test]
+    fn screen_space_nodes_animate_while_paused() {
+        // Pausing stops the scaled time but not the unscaled one.
+        let mut world = create_world(Duration::ZERO, Duration::from_millis(10));
+        let world_node = create_node(&mut world, "world").build();
+        let screen_node = create_node(&mut world, "menu")
+            .with(ScreenSpace {
+                anchor: Anchor::TopLeft,
+            })
+            .build();
+
+        run(&mut world);
+        assert_eq!(velocity(&world, world_node), Point::new(0, 0));
+        assert_eq!(velocity(&world, screen_node), Point::new(1, 0));
+    }
+
+    #[test]
+    fn nodes_with_missing_sprites_are_skipped() {
+        let mut world = create_world(Duration::from_millis(10), Duration::from_millis(10));
+        let node = create_node(&mut world, "ghost")
+            .with(SpriteInfo {
+                sprite_id: "missing".to_owned(),
+                texture_id: "missing".to_owned(),
+                frame_index: 0,
+                bounding_box: Rect::new(0, 0, 4, 4),
+                flip: TileFlip::default(),
+                sprite_ref: None,
+            })
+            .build();
+
+        run(&mut world);
+        assert_eq!(velocity(&world, node), Point::new(0, 0));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     action::ActionQueue,
-    components::{Collisions, Id, Position, SpriteInfo},
+    components::{Collisions, Id, Position, ScreenSpace, ShapeInfo, SpriteInfo},
     physics::{CollisionChecker, CollisionNode},
     resources::SpriteManager,
 };
@@ -14,7 +14,9 @@ pub struct CollisionSystemData<'a> {
     ids: ReadStorage<'a, Id>,
     positions: ReadStorage<'a, Position>,
     sprite_info: ReadStorage<'a, SpriteInfo>,
+    shapes: ReadStorage<'a, ShapeInfo>,
     collisions: ReadStorage<'a, Collisions>,
+    screen_space: ReadStorage<'a, ScreenSpace>,
 }
 
 pub struct CollisionSystem {
@@ -34,33 +36,43 @@ impl<'a> System<'a> for CollisionSystem {
 
     fn run(&mut self, data: Self::SystemData) {
         // let mut data = data;
-        for (lhs_entity, lhs_id, lhs_position, lhs_sprite_info, collisions) in (
+        // Screen space nodes are not part of the world.
+        for (lhs_entity, lhs_id, lhs_position, lhs_sprite_info, lhs_shape_info, collisions, _) in (
             &data.entities,
             &data.ids,
             &data.positions,
-            &data.sprite_info,
+            (&data.sprite_info).maybe(),
+            (&data.shapes).maybe(),
             &data.collisions,
+            !&data.screen_space,
         )
             .join()
         {
+            if !collidable(lhs_sprite_info, lhs_shape_info) {
+                continue;
+            }
+
             let lhs_node = CollisionNode {
                 entity_id: lhs_entity.id(),
                 id: lhs_id,
                 position: lhs_position,
-                collision_mask: data
-                    .sprite_manager
-                    .get_collision_mask(&lhs_sprite_info.texture_id, lhs_sprite_info.frame_index),
+                collision_mask: lhs_sprite_info.and_then(|sprite_info| {
+                    data.sprite_manager
+                        .get_collision_mask(&sprite_info.sprite_id, sprite_info.frame_index)
+                }),
             };
 
-            for (rhs_entity, rhs_id, rhs_position, rhs_sprite_info) in (
+            for (rhs_entity, rhs_id, rhs_position, rhs_sprite_info, rhs_shape_info, _) in (
                 &data.entities,
                 &data.ids,
                 &data.positions,
-                &data.sprite_info,
+                (&data.sprite_info).maybe(),
+                (&data.shapes).maybe(),
+                !&data.screen_space,
             )
                 .join()
             {
-                if lhs_entity == rhs_entity {
+                if lhs_entity == rhs_entity || !collidable(rhs_sprite_info, rhs_shape_info) {
                     continue;
                 }
 
@@ -70,10 +82,10 @@ impl<'a> System<'a> for CollisionSystem {
                         entity_id: rhs_entity.id(),
                         id: rhs_id,
                         position: rhs_position,
-                        collision_mask: data.sprite_manager.get_collision_mask(
-                            &rhs_sprite_info.texture_id,
-                            rhs_sprite_info.frame_index,
-                        ),
+                        collision_mask: rhs_sprite_info.and_then(|sprite_info| {
+                            data.sprite_manager
+                                .get_collision_mask(&sprite_info.sprite_id, sprite_info.frame_index)
+                        }),
                     },
                     &collisions.on_collision,
                 );
@@ -81,3 +93,9 @@ impl<'a> System<'a> for CollisionSystem {
         }
     }
 }
+
+/// Sprites always take part in collisions, while shapes only when marked as
+/// collidable, in which case their bounding box is used.
+fn collidable(sprite_info: Option<&SpriteInfo>, shape_info: Option<&ShapeInfo>) -> bool {
+    sprite_info.is_some() || shape_info.is_some_and(|shape_info| shape_info.shape.collidable)
+}
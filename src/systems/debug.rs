@@ -0,0 +1,242 @@
+use crate::{
+    components::{Animation, Id, Position, PreviousPosition, RigidBody, ScreenSpace, SpriteInfo},
+    core::{FrameStats, Status},
+    crust::DebugInfo,
+    resources::{Interpolation, Orientation, SpriteManager, Viewport, WorldSize},
+    scene::SceneManager,
+};
+use sdl2::{
+    gfx::primitives::DrawRenderer,
+    pixels::Color,
+    rect::{Point, Rect},
+    render::WindowCanvas,
+};
+use specs::prelude::*;
+
+#[derive(SystemData)]
+pub struct DebugOverlayData<'a> {
+    debug: ReadExpect<'a, DebugInfo>,
+    viewport: ReadExpect<'a, Viewport>,
+    world_size: ReadExpect<'a, WorldSize>,
+    frame_stats: ReadExpect<'a, FrameStats>,
+    sprite_manager: ReadExpect<'a, SpriteManager>,
+    interpolation: ReadExpect<'a, Interpolation>,
+
+    entities: Entities<'a>,
+    ids: ReadStorage<'a, Id>,
+    positions: ReadStorage<'a, Position>,
+    previous_positions: ReadStorage<'a, PreviousPosition>,
+    sprite_info: ReadStorage<'a, SpriteInfo>,
+    rigid_bodies: ReadStorage<'a, RigidBody>,
+    animations: ReadStorage<'a, Animation>,
+    screen_space: ReadStorage<'a, ScreenSpace>,
+}
+
+/// Draws the debug overlay configured in DebugInfo on top of the rendered
+/// frame.
+///
+/// Screen space nodes are not part of the world and are skipped.
+pub fn render_debug_overlay(
+    canvas: &mut WindowCanvas,
+    scene_manager: &SceneManager,
+    data: DebugOverlayData,
+) -> Result<(), Status> {
+    // Drawn at the interpolated positions the nodes are rendered at.
+    let viewport = data.interpolation.viewport(data.viewport.0);
+    let node_on_screen = |entity: Entity, position: &Position| {
+        let position = match data.previous_positions.get(entity) {
+            Some(previous) => data.interpolation.rect(previous.0, position.0),
+            None => position.0,
+        };
+        to_screen(position, viewport)
+    };
+
+    if data.debug.draw_tile_grid {
+        draw_tile_grid(canvas, scene_manager, viewport)?;
+    }
+
+    if data.debug.draw_world_bounds {
+        draw_world_bounds(canvas, data.world_size.0, viewport)?;
+    }
+
+    if data.debug.draw_collision_masks {
+        canvas.set_draw_color(Color::RED);
+        for (entity, position, sprite_info, _) in (
+            &data.entities,
+            &data.positions,
+            &data.sprite_info,
+            !&data.screen_space,
+        )
+            .join()
+        {
+            if let Some(mask) = data
+                .sprite_manager
+                .get_collision_mask(&sprite_info.sprite_id, sprite_info.frame_index)
+            {
+                let aabb = node_on_screen(entity, position);
+                let mut points = vec![];
+                for bit in mask {
+                    points.push(Point::new(
+                        aabb.x() + (bit % aabb.width()) as i32,
+                        aabb.y() + (bit / aabb.width()) as i32,
+                    ));
+                }
+                canvas.draw_points(points.as_slice())?;
+            }
+        }
+    }
+
+    if data.debug.draw_bounding_boxes {
+        canvas.set_draw_color(Color::MAGENTA);
+        for (entity, position, _) in (&data.entities, &data.positions, !&data.screen_space).join() {
+            canvas.draw_rect(node_on_screen(entity, position))?;
+        }
+    }
+
+    if data.debug.draw_rigid_bodies {
+        canvas.set_draw_color(Color::CYAN);
+        for (entity, position, _, _) in (
+            &data.entities,
+            &data.positions,
+            &data.rigid_bodies,
+            !&data.screen_space,
+        )
+            .join()
+        {
+            let aabb = node_on_screen(entity, position);
+            canvas.draw_line(aabb.top_left(), aabb.bottom_right())?;
+            canvas.draw_line(aabb.top_right(), aabb.bottom_left())?;
+        }
+    }
+
+    if data.debug.show_node_ids || data.debug.show_animation_scripts {
+        for (entity, id, position, animation, _) in (
+            &data.entities,
+            &data.ids,
+            &data.positions,
+            (&data.animations).maybe(),
+            !&data.screen_space,
+        )
+            .join()
+        {
+            let aabb = node_on_screen(entity, position);
+            let mut y = aabb.y() - LINE_HEIGHT;
+
+            if data.debug.show_animation_scripts {
+                if let Some(animation) = animation {
+                    canvas.string(
+                        aabb.x() as i16,
+                        y as i16,
+                        &format!(
+                            "{}[{}]",
+                            animation.runner.script.id,
+                            animation.runner.current_step()
+                        ),
+                        Color::YELLOW,
+                    )?;
+                    y -= LINE_HEIGHT;
+                }
+            }
+
+            if data.debug.show_node_ids && !id.0.is_empty() {
+                canvas.string(aabb.x() as i16, y as i16, &id.0, Color::WHITE)?;
+            }
+        }
+    }
+
+    if data.debug.show_fps {
+        draw_frame_stats(canvas, &data.frame_stats)?;
+    }
+
+    Ok(())
+}
+
+fn draw_tile_grid(
+    canvas: &mut WindowCanvas,
+    scene_manager: &SceneManager,
+    viewport: Rect,
+) -> Result<(), Status> {
+    let (tile_width, tile_height) = scene_manager.tile_size();
+    if tile_width == 0 || tile_height == 0 {
+        return Ok(());
+    }
+    // Tiles of other orientations do not line up in rows and columns.
+    match scene_manager.grid().map(|grid| grid.orientation()) {
+        Some(Orientation::Orthogonal) => {}
+        _ => return Ok(()),
+    }
+
+    let bounds = to_screen(scene_manager.scene_bounds(), viewport);
+    canvas.set_draw_color(Color::GREY);
+
+    for column in 0..=bounds.width() / tile_width {
+        let x = bounds.left() + (column * tile_width) as i32;
+        if 0 <= x && x <= viewport.width() as i32 {
+            canvas.draw_line(Point::new(x, bounds.top()), Point::new(x, bounds.bottom()))?;
+        }
+    }
+    for row in 0..=bounds.height() / tile_height {
+        let y = bounds.top() + (row * tile_height) as i32;
+        if 0 <= y && y <= viewport.height() as i32 {
+            canvas.draw_line(Point::new(bounds.left(), y), Point::new(bounds.right(), y))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn draw_world_bounds(
+    canvas: &mut WindowCanvas,
+    world_bounds: Rect,
+    viewport: Rect,
+) -> Result<(), Status> {
+    canvas.set_draw_color(Color::GREEN);
+    canvas.draw_rect(to_screen(world_bounds, viewport))?;
+
+    canvas.string(
+        4,
+        (viewport.height() as i32 - LINE_HEIGHT - 4) as i16,
+        &format!(
+            "viewport: ({}, {}) {}x{}  world: ({}, {}) {}x{}",
+            viewport.x(),
+            viewport.y(),
+            viewport.width(),
+            viewport.height(),
+            world_bounds.x(),
+            world_bounds.y(),
+            world_bounds.width(),
+            world_bounds.height(),
+        ),
+        Color::GREEN,
+    )?;
+
+    Ok(())
+}
+
+fn draw_frame_stats(canvas: &mut WindowCanvas, stats: &FrameStats) -> Result<(), Status> {
+    canvas.string(
+        4,
+        4,
+        &format!(
+            "fps: {} (best: {} msec, worst: {} msec)",
+            stats.fps,
+            stats.best_frame_duration.as_millis(),
+            stats.worst_frame_duration.as_millis(),
+        ),
+        Color::WHITE,
+    )?;
+
+    Ok(())
+}
+
+fn to_screen(rect: Rect, viewport: Rect) -> Rect {
+    Rect::new(
+        rect.x() - viewport.x(),
+        rect.y() - viewport.y(),
+        rect.width(),
+        rect.height(),
+    )
+}
+
+// Height of the SDL2_gfx built-in font plus spacing.
+const LINE_HEIGHT: i32 = 10;
@@ -0,0 +1,42 @@
+use crate::{
+    components::{Position, PreviousPosition},
+    resources::{Interpolation, Viewport},
+};
+use log::error;
+use specs::prelude::*;
+
+#[derive(SystemData)]
+pub struct InterpolationSystemData<'a> {
+    entities: Entities<'a>,
+    viewport: ReadExpect<'a, Viewport>,
+    interpolation: WriteExpect<'a, Interpolation>,
+
+    positions: ReadStorage<'a, Position>,
+    previous_positions: WriteStorage<'a, PreviousPosition>,
+}
+
+/// Keeps the state before each simulation step that rendering interpolates
+/// from. It needs to run before any system that moves nodes or the viewport.
+pub struct InterpolationSystem;
+
+impl<'a> System<'a> for InterpolationSystem {
+    type SystemData = InterpolationSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        for (entity, position) in (&data.entities, &data.positions).join() {
+            if let Err(e) = data
+                .previous_positions
+                .insert(entity, PreviousPosition(position.0.top_left()))
+            {
+                error!("{}", e);
+            }
+        }
+        data.interpolation.previous_viewport = Some(data.viewport.0);
+    }
+}
+
+impl InterpolationSystem {
+    pub fn new() -> Self {
+        InterpolationSystem {}
+    }
+}
@@ -1,11 +1,18 @@
 mod animator;
 mod collisions;
+mod debug;
+mod interpolation;
 mod movement;
+mod particles;
 mod renderer;
 mod scrolling;
+mod tile_animation;
 
 pub use animator::AnimatorSystem;
 pub use collisions::CollisionSystem;
+pub use interpolation::InterpolationSystem;
 pub use movement::MovementSystem;
+pub use particles::ParticleSystem;
 pub use renderer::render;
 pub use scrolling::ScrollingSystem;
+pub use tile_animation::TileAnimationSystem;
@@ -1,5 +1,5 @@
 use crate::{
-    components::{Id, Position, RigidBody, SpriteInfo, Velocity},
+    components::{Id, Position, RigidBody, ScreenSpace, ShapeInfo, SpriteInfo, Velocity},
     physics::CollisionNode,
     resources::SpriteManager,
 };
@@ -14,7 +14,9 @@ pub struct MovementSystemData<'a> {
     positions: WriteStorage<'a, Position>,
     velocities: WriteStorage<'a, Velocity>,
     sprite_info: ReadStorage<'a, SpriteInfo>,
+    shapes: ReadStorage<'a, ShapeInfo>,
     rigid_bodies: ReadStorage<'a, RigidBody>,
+    screen_space: ReadStorage<'a, ScreenSpace>,
 }
 
 pub struct MovementSystem {
@@ -35,14 +37,17 @@ impl<'a> System<'a> for MovementSystem {
     fn run(&mut self, mut data: Self::SystemData) {
         let mut dirty = BitSet::new();
 
-        // First Join captures Sprites only. Background tiles do not have a
-        // SpriteInfo component.
-        for (lhs_entity, lhs_position, lhs_velocity, lhs_sprite_info, _) in (
+        // First Join captures scene nodes only, i.e. sprites and shapes.
+        // Background tiles do not have a Velocity component. Screen space nodes
+        // are not part of the world.
+        for (lhs_entity, lhs_position, lhs_velocity, lhs_sprite_info, lhs_shape, _, _) in (
             &data.entities,
             &data.positions,
             &mut data.velocities,
-            &data.sprite_info,
+            (&data.sprite_info).maybe(),
+            (&data.shapes).maybe(),
             &data.rigid_bodies,
+            !&data.screen_space,
         )
             .join()
         {
@@ -50,17 +55,22 @@ impl<'a> System<'a> for MovementSystem {
                 continue;
             }
             dirty.add(lhs_entity.id());
+            if !solid(lhs_shape) {
+                continue;
+            }
 
             // Second Join captures any rigid body including tiles.
-            for (rhs_entity, rhs_position, rhs_sprite_info, _) in (
+            for (rhs_entity, rhs_position, rhs_sprite_info, rhs_shape, _, _) in (
                 &data.entities,
                 &data.positions,
                 (&data.sprite_info).maybe(),
+                (&data.shapes).maybe(),
                 &data.rigid_bodies,
+                !&data.screen_space,
             )
                 .join()
             {
-                if lhs_entity == rhs_entity {
+                if lhs_entity == rhs_entity || !solid(rhs_shape) {
                     continue;
                 }
 
@@ -71,7 +81,7 @@ impl<'a> System<'a> for MovementSystem {
                     collision_mask: match rhs_sprite_info {
                         Some(sprite_info) => data
                             .sprite_manager
-                            .get_collision_mask(&sprite_info.texture_id, sprite_info.frame_index),
+                            .get_collision_mask(&sprite_info.sprite_id, sprite_info.frame_index),
                         None => None,
                     },
                 };
@@ -84,10 +94,10 @@ impl<'a> System<'a> for MovementSystem {
                         entity_id: lhs_entity.id(),
                         id: &self.null_id,
                         position: &Position(projected_position),
-                        collision_mask: data.sprite_manager.get_collision_mask(
-                            &lhs_sprite_info.texture_id,
-                            lhs_sprite_info.frame_index,
-                        ),
+                        collision_mask: lhs_sprite_info.and_then(|sprite_info| {
+                            data.sprite_manager
+                                .get_collision_mask(&sprite_info.sprite_id, sprite_info.frame_index)
+                        }),
                     };
 
                     if let None = lhs.intersection(&rhs) {
@@ -118,10 +128,17 @@ impl<'a> System<'a> for MovementSystem {
     }
 }
 
+/// Shapes only block movement when they are collidable, as in collision
+/// checks. Other rigid bodies always do.
+fn solid(shape_info: Option<&ShapeInfo>) -> bool {
+    shape_info.is_none_or(|shape_info| shape_info.shape.collidable)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::resources::{Frame, Sprite};
+    use crate::crust::{shape, RectangleShape, Shape};
+    use crate::resources::{Frame, Sprite, TileFlip};
     use sdl2::rect::Rect;
 
     fn create_world() -> World {
@@ -129,7 +146,9 @@ mod tests {
         w.register::<Position>();
         w.register::<Velocity>();
         w.register::<SpriteInfo>();
+        w.register::<ShapeInfo>();
         w.register::<RigidBody>();
+        w.register::<ScreenSpace>();
 
         let sprite_manager = SpriteManager::mock(vec![
             Sprite {
@@ -138,6 +157,7 @@ mod tests {
                     bounding_box: Rect::new(0, 0, 5, 3),
                     bitmask: Some(vec![0, 5, 8, 9, 10, 11, 12, 13].iter().collect()),
                 }],
+                tiles: vec![],
             },
             Sprite {
                 texture_id: "spriteB".to_owned(),
@@ -145,6 +165,7 @@ mod tests {
                     bounding_box: Rect::new(0, 0, 5, 3),
                     bitmask: Some(vec![0, 1, 6, 7, 10, 11, 12, 13, 14].iter().collect()),
                 }],
+                tiles: vec![],
             },
         ]);
         w.insert(sprite_manager);
@@ -158,9 +179,12 @@ mod tests {
             .with(Position(Rect::new(position.x(), position.y(), 5, 3)))
             .with(Velocity(velocity))
             .with(SpriteInfo {
+                sprite_id: "spriteA".to_owned(),
                 texture_id: "spriteA".to_owned(),
                 frame_index: 0,
                 bounding_box: Rect::new(0, 0, 5, 3),
+                flip: TileFlip::default(),
+                sprite_ref: None,
             })
             .with(RigidBody {})
             .build()
@@ -276,6 +300,88 @@ mod tests {
         assert_eq!(velocities.get(sprite).unwrap().0, Point::new(0, 0));
     }
 
+    #[test]
+    fn screen_space_nodes_do_not_block_movement() {
+        let mut world = create_world();
+        let sprite = create_sprite(&mut world, Point::new(0, 0), Point::new(2, 0));
+        let hud = create_sprite(&mut world, Point::new(5, 0), Point::new(0, 0));
+        world
+            .write_storage::<ScreenSpace>()
+            .insert(
+                hud,
+                ScreenSpace {
+                    anchor: crate::crust::Anchor::TopLeft,
+                },
+            )
+            .unwrap();
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(MovementSystem::new(), "move", &[])
+            .build();
+        dispatcher.dispatch(&mut world);
+        world.maintain();
+
+        let positions = world.read_storage::<Position>();
+        assert_eq!(positions.get(sprite).unwrap().0, Rect::new(2, 0, 5, 3));
+        assert_eq!(positions.get(hud).unwrap().0, Rect::new(5, 0, 5, 3));
+    }
+
+    #[test]
+    fn shape_nodes_collide_by_bounding_box() {
+        let mut world = create_world();
+        let shape = create_shape(&mut world, Point::new(0, 0), Point::new(3, 0), true);
+        create_shape(&mut world, Point::new(6, 0), Point::new(0, 0), true);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(MovementSystem::new(), "move", &[])
+            .build();
+        dispatcher.dispatch(&mut world);
+        world.maintain();
+
+        let positions = world.read_storage::<Position>();
+        assert_eq!(positions.get(shape).unwrap().0, Rect::new(1, 0, 5, 3));
+    }
+
+    #[test]
+    fn non_collidable_shapes_do_not_block() {
+        let mut world = create_world();
+        let shape = create_shape(&mut world, Point::new(0, 0), Point::new(3, 0), true);
+        create_shape(&mut world, Point::new(6, 0), Point::new(0, 0), false);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(MovementSystem::new(), "move", &[])
+            .build();
+        dispatcher.dispatch(&mut world);
+        world.maintain();
+
+        let positions = world.read_storage::<Position>();
+        assert_eq!(positions.get(shape).unwrap().0, Rect::new(3, 0, 5, 3));
+    }
+
+    fn create_shape(
+        world: &mut World,
+        position: Point,
+        velocity: Point,
+        collidable: bool,
+    ) -> Entity {
+        world
+            .create_entity()
+            .with(Position(Rect::new(position.x(), position.y(), 5, 3)))
+            .with(Velocity(velocity))
+            .with(ShapeInfo {
+                shape: Shape {
+                    shape: Some(shape::Shape::Rectangle(RectangleShape {
+                        width: 5,
+                        height: 3,
+                    })),
+                    collidable,
+                    ..Default::default()
+                },
+            })
+            .with(RigidBody {})
+            .build()
+    }
+
     #[test]
     fn rigid_bodies_with_collision_masks() {
         // Sprites with collision masks below colliding.
@@ -289,9 +395,12 @@ mod tests {
             .create_entity()
             .with(Position(Rect::new(5, 0, 5, 3)))
             .with(SpriteInfo {
+                sprite_id: "spriteB".to_owned(),
                 texture_id: "spriteB".to_owned(),
                 frame_index: 0,
                 bounding_box: Rect::new(0, 0, 5, 3),
+                flip: TileFlip::default(),
+                sprite_ref: None,
             })
             .with(RigidBody {})
             .build();
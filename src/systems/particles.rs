@@ -0,0 +1,32 @@
+use crate::components::{ParticleEmitters, Position};
+use specs::prelude::*;
+use std::time::Duration;
+
+#[derive(SystemData)]
+pub struct ParticleSystemData<'a> {
+    time_since_last_frame: ReadExpect<'a, Duration>,
+
+    positions: ReadStorage<'a, Position>,
+    emitters: WriteStorage<'a, ParticleEmitters>,
+}
+
+pub struct ParticleSystem;
+
+impl<'a> System<'a> for ParticleSystem {
+    type SystemData = ParticleSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        for (position, emitters) in (&data.positions, &mut data.emitters).join() {
+            for emitter in &mut emitters.emitters {
+                emitter.update(*data.time_since_last_frame, position.0.top_left());
+            }
+            emitters.emitters.retain(|emitter| !emitter.finished());
+        }
+    }
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {}
+    }
+}
@@ -1,63 +1,279 @@
+use super::debug::{render_debug_overlay, DebugOverlayData};
 use crate::{
-    components::{Position, Rotation, SpriteInfo},
+    components::{
+        Depth, ParticleEmitters, Position, PreviousPosition, Rotation, ScreenSpace, ShapeInfo,
+        SpriteInfo,
+    },
     core::Status,
-    crust::DebugInfo,
-    resources::{TextureManager, Viewport},
-    scene::SceneManager,
+    crust::shape,
+    resources::{
+        set_texture_colour, Interpolation, PendingScreenshots, TextureManager, Viewport, WindowSize,
+    },
+    scene::{SceneManager, TileAnimations},
+};
+use log::error;
+use sdl2::{
+    gfx::primitives::DrawRenderer,
+    image::SaveSurface,
+    pixels::{Color, PixelFormatEnum},
+    rect::{Point, Rect},
+    render::WindowCanvas,
+    surface::Surface,
 };
-use sdl2::{pixels::Color, rect::Rect, render::WindowCanvas};
 use specs::prelude::*;
 
-type SystemData<'a> = (
-    ReadExpect<'a, DebugInfo>,
-    ReadExpect<'a, Viewport>,
-    ReadStorage<'a, Position>,
-    ReadStorage<'a, Rotation>,
-    ReadStorage<'a, SpriteInfo>,
-);
+#[derive(SystemData)]
+pub struct RendererSystemData<'a> {
+    entities: Entities<'a>,
+    debug_overlay: DebugOverlayData<'a>,
+    viewport: ReadExpect<'a, Viewport>,
+    interpolation: ReadExpect<'a, Interpolation>,
+    window_size: ReadExpect<'a, WindowSize>,
+    screenshots: WriteExpect<'a, PendingScreenshots>,
+    tile_animations: ReadExpect<'a, TileAnimations>,
+
+    positions: ReadStorage<'a, Position>,
+    previous_positions: ReadStorage<'a, PreviousPosition>,
+    rotations: ReadStorage<'a, Rotation>,
+    sprite_info: ReadStorage<'a, SpriteInfo>,
+    shapes: ReadStorage<'a, ShapeInfo>,
+    depths: ReadStorage<'a, Depth>,
+    emitters: ReadStorage<'a, ParticleEmitters>,
+    screen_space: ReadStorage<'a, ScreenSpace>,
+}
 
 pub fn render(
     canvas: &mut WindowCanvas,
     scene_manager: &SceneManager,
     texture_manager: &mut TextureManager<sdl2::video::WindowContext>,
-    (debug, viewport, positions, rotations, sprite_info): SystemData,
+    mut data: RendererSystemData,
 ) -> Result<(), Status> {
+    // Rendering happens in between simulation steps.
+    let viewport = data.interpolation.viewport(data.viewport.0);
+
+    canvas.set_draw_color(Color::BLACK);
     canvas.clear();
 
-    scene_manager.render(viewport.0, canvas, texture_manager)?;
+    scene_manager.render(viewport, canvas, texture_manager, &data.tile_animations)?;
+
+    let mut world_nodes = vec![];
+    let mut screen_nodes = vec![];
+    for (entity, depth, _, screen_space) in (
+        &data.entities,
+        (&data.depths).maybe(),
+        data.sprite_info.mask() | data.shapes.mask(),
+        (&data.screen_space).maybe(),
+    )
+        .join()
+    {
+        let depth = depth.map_or(0, |depth| depth.0);
+        match screen_space {
+            Some(_) => screen_nodes.push((depth, entity)),
+            None => world_nodes.push((depth, entity)),
+        }
+    }
+    // Sorting is stable so nodes with the same depth are drawn in entity order.
+    // Entity ids of deleted nodes are reused, so this is not necessarily the
+    // order they were created in; nodes that must overlap in a given order need
+    // distinct depths.
+    world_nodes.sort_by_key(|(depth, _)| *depth);
+    screen_nodes.sort_by_key(|(depth, _)| *depth);
+
+    for (_, entity) in world_nodes {
+        if let Some(position) = data.positions.get(entity) {
+            let position = match data.previous_positions.get(entity) {
+                Some(previous) => data.interpolation.rect(previous.0, position.0),
+                None => position.0,
+            };
+            let aabb = Rect::new(
+                position.x() - viewport.x(),
+                position.y() - viewport.y(),
+                position.width(),
+                position.height(),
+            );
+            draw_node(canvas, texture_manager, &data, entity, aabb)?;
+        }
+    }
+
+    for emitters in (&data.emitters).join() {
+        for emitter in &emitters.emitters {
+            let texture = texture_manager.load(&emitter.texture_id)?;
+            for particle in &emitter.particles {
+                set_texture_colour(&texture, emitter.colour(particle));
+
+                let aabb = emitter.aabb(particle);
+                let aabb = data
+                    .interpolation
+                    .rect(aabb.top_left() - particle.last_step(), aabb);
+                canvas.copy(
+                    &texture,
+                    emitter.frame,
+                    Rect::new(
+                        aabb.x() - viewport.x(),
+                        aabb.y() - viewport.y(),
+                        aabb.width(),
+                        aabb.height(),
+                    ),
+                )?;
+            }
+            // Textures are shared with sprites.
+            set_texture_colour(&texture, Color::WHITE);
+        }
+    }
+
+    // Screen space nodes are drawn after the world ignoring the viewport.
+    for (_, entity) in screen_nodes {
+        if let (Some(position), Some(screen_space)) =
+            (data.positions.get(entity), data.screen_space.get(entity))
+        {
+            let aabb = screen_space.screen_position(position.0, data.window_size.0);
+            draw_node(canvas, texture_manager, &data, entity, aabb)?;
+        }
+    }
+
+    render_debug_overlay(canvas, scene_manager, data.debug_overlay)?;
+
+    for filename in data.screenshots.0.drain(..) {
+        if let Err(e) = save_screenshot(canvas, &filename) {
+            error!("Failed to save screenshot '{filename}': {e}");
+        }
+    }
+
+    canvas.present();
 
-    for (position, rotation, sprite_info) in (&positions, &rotations, &sprite_info).join() {
+    Ok(())
+}
+
+/// Draws the sprite or shape of a scene node on the `aabb` of the canvas.
+fn draw_node(
+    canvas: &mut WindowCanvas,
+    texture_manager: &mut TextureManager<sdl2::video::WindowContext>,
+    data: &RendererSystemData,
+    entity: Entity,
+    aabb: Rect,
+) -> Result<(), Status> {
+    if let Some(sprite_info) = data.sprite_info.get(entity) {
         let texture = texture_manager.load(&sprite_info.texture_id)?;
+        let rotation = data.rotations.get(entity);
+        let (flip_angle, flip_horizontal, flip_vertical) = sprite_info.flip.transform();
 
         canvas.copy_ex(
             &texture,
             sprite_info.bounding_box,
-            Rect::new(
-                position.0.x() - viewport.0.x(),
-                position.0.y() - viewport.0.y(),
-                position.0.width(),
-                position.0.height(),
-            ),
-            rotation.angle,
-            rotation.centre,
-            false,
-            false,
+            aabb,
+            rotation.map_or(0.0, |rotation| rotation.angle) + flip_angle,
+            rotation.and_then(|rotation| rotation.centre),
+            flip_horizontal,
+            flip_vertical,
         )?;
+    } else if let Some(shape_info) = data.shapes.get(entity) {
+        draw_shape(canvas, shape_info, aabb)?;
     }
 
-    if debug.draw_bounding_boxes {
-        for position in (&positions).join() {
-            canvas.set_draw_color(Color::MAGENTA);
-            canvas.draw_rect(Rect::new(
-                position.0.x() - viewport.0.x(),
-                position.0.y() - viewport.0.y(),
-                position.0.width(),
-                position.0.height(),
-            ))?;
+    Ok(())
+}
+
+/// Draws a shape stretched over the `aabb` of the canvas, which differs from
+/// the shape's own bounding box when the node is scaled.
+fn draw_shape(canvas: &WindowCanvas, shape_info: &ShapeInfo, aabb: Rect) -> Result<(), Status> {
+    let colour = shape_info.colour();
+    let filled = shape_info.shape.filled;
+
+    let bbox = shape_info.bounding_box();
+    let scale = (
+        aabb.width() as f64 / bbox.width() as f64,
+        aabb.height() as f64 / bbox.height() as f64,
+    );
+    let transform = |point: &Point| {
+        (
+            (aabb.x() as f64 + point.x() as f64 * scale.0) as i16,
+            (aabb.y() as f64 + point.y() as f64 * scale.1) as i16,
+        )
+    };
+
+    // SDL2_gfx corner coordinates are inclusive.
+    let (x1, y1) = (aabb.left() as i16, aabb.top() as i16);
+    let (x2, y2) = ((aabb.right() - 1) as i16, (aabb.bottom() - 1) as i16);
+
+    match &shape_info.shape.shape {
+        Some(shape::Shape::Rectangle(_)) => match filled {
+            true => canvas.box_(x1, y1, x2, y2, colour)?,
+            false => canvas.rectangle(x1, y1, x2, y2, colour)?,
+        },
+        Some(shape::Shape::RoundedBox(rounded_box)) => {
+            let radius = (rounded_box.radius as f64 * scale.0.min(scale.1)) as i16;
+            match filled {
+                true => canvas.rounded_box(x1, y1, x2, y2, radius, colour)?,
+                false => canvas.rounded_rectangle(x1, y1, x2, y2, radius, colour)?,
+            }
+        }
+        Some(shape::Shape::Circle(_)) => {
+            let centre = aabb.center();
+            let radius = (aabb.width().min(aabb.height()).saturating_sub(1) / 2) as i16;
+            match filled {
+                true => {
+                    canvas.filled_circle(centre.x() as i16, centre.y() as i16, radius, colour)?
+                }
+                false => canvas.circle(centre.x() as i16, centre.y() as i16, radius, colour)?,
+            }
+        }
+        Some(shape::Shape::Line(line)) => {
+            let points = shape_info.points();
+            if let [from, to] = points.as_slice() {
+                let (from, to) = (transform(from), transform(to));
+                match line.thickness > 1 {
+                    true => canvas.thick_line(
+                        from.0,
+                        from.1,
+                        to.0,
+                        to.1,
+                        line.thickness.min(u8::MAX as u32) as u8,
+                        colour,
+                    )?,
+                    false => canvas.line(from.0, from.1, to.0, to.1, colour)?,
+                }
+            }
         }
+        Some(shape::Shape::Polygon(_)) => {
+            let (vx, vy): (Vec<i16>, Vec<i16>) = shape_info.points().iter().map(transform).unzip();
+            match filled {
+                true => canvas.filled_polygon(&vx, &vy, colour)?,
+                false => canvas.polygon(&vx, &vy, colour)?,
+            }
+        }
+        None => {}
     }
 
-    canvas.present();
+    Ok(())
+}
+
+/// Saves the contents of the canvas as PNG. It needs to be called before the
+/// canvas is presented, as the contents of the back buffer are undefined
+/// after that.
+fn save_screenshot(canvas: &mut WindowCanvas, filename: &str) -> Result<(), Status> {
+    // SDL only reads pixels within the viewport, which a logical resolution
+    // shrinks to the scaled frame. It is lifted during the read so that the
+    // screenshot covers the whole output, including the letterbox bars.
+    let (logical_width, logical_height) = canvas.logical_size();
+    let letterboxed = logical_width > 0 && logical_height > 0;
+    if letterboxed {
+        canvas
+            .set_logical_size(0, 0)
+            .map_err(|e| Status::new("Failed to reset logical size", e))?;
+    }
+    let (width, height) = canvas.output_size()?;
+    let format = PixelFormatEnum::ABGR8888;
+    let pixels = canvas.read_pixels(Rect::new(0, 0, width, height), format);
+    if letterboxed {
+        canvas
+            .set_logical_size(logical_width, logical_height)
+            .map_err(|e| Status::new("Failed to restore logical size", e))?;
+    }
+    let mut pixels = pixels?;
+
+    let pitch = width * format.byte_size_per_pixel() as u32;
+    let surface = Surface::from_data(&mut pixels, width, height, pitch, format)?;
+    surface.save(filename)?;
 
     Ok(())
 }
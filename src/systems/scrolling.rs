@@ -1,6 +1,6 @@
 use crate::{
     components::ScrollingInfo,
-    resources::{Viewport, WorldSize},
+    resources::{UnscaledTime, Viewport, WorldSize},
 };
 use sdl2::rect::Point;
 use specs::prelude::*;
@@ -9,6 +9,7 @@ use std::time::Duration;
 #[derive(SystemData)]
 pub struct ScrollingSystemData<'a> {
     time_since_last_frame: ReadExpect<'a, Duration>,
+    step: ReadExpect<'a, UnscaledTime>,
     world_size: WriteExpect<'a, WorldSize>,
     viewport: WriteExpect<'a, Viewport>,
 
@@ -23,23 +24,27 @@ impl<'a> System<'a> for ScrollingSystem {
     fn run(&mut self, data: Self::SystemData) {
         let mut data = data;
 
+        // Scrolling moves the viewport by the scrolling direction once per
+        // simulation step at the configured rate, so that it slows down and
+        // speeds up with the time scale.
+        let scroll_delay = data.step.0;
+        if scroll_delay.is_zero() {
+            return;
+        }
+
         for scrolling in (&mut data.scrolling_info).join() {
             scrolling.wait_time += *data.time_since_last_frame;
-            while SCROLL_DELAY <= scrolling.wait_time {
-                scrolling.wait_time -= SCROLL_DELAY;
+            while scroll_delay <= scrolling.wait_time {
+                scrolling.wait_time -= scroll_delay;
                 data.viewport
                     .0
                     .offset(scrolling.direction.x(), scrolling.direction.y());
 
+                let world = data.world_size.0;
+                let viewport = data.viewport.0;
                 let pos = Point::new(
-                    data.viewport.0.x().clamp(
-                        0,
-                        (data.world_size.0.width() - data.viewport.0.width()) as i32,
-                    ),
-                    data.viewport.0.y().clamp(
-                        0,
-                        (data.world_size.0.height() - data.viewport.0.height()) as i32,
-                    ),
+                    clamp_to_world(viewport.x(), viewport.width(), world.left(), world.right()),
+                    clamp_to_world(viewport.y(), viewport.height(), world.top(), world.bottom()),
                 );
                 data.viewport.0.reposition(pos);
             }
@@ -53,4 +58,8 @@ impl ScrollingSystem {
     }
 }
 
-const SCROLL_DELAY: Duration = Duration::from_millis(10);
+// Keeps a viewport axis inside the world, whose origin can be negative in
+// infinite maps.
+fn clamp_to_world(position: i32, size: u32, world_start: i32, world_end: i32) -> i32 {
+    position.clamp(world_start, (world_end - size as i32).max(world_start))
+}
@@ -0,0 +1,28 @@
+use crate::scene::TileAnimations;
+use specs::prelude::*;
+use std::time::Duration;
+
+#[derive(SystemData)]
+pub struct TileAnimationSystemData<'a> {
+    time_since_last_frame: ReadExpect<'a, Duration>,
+    tile_animations: WriteExpect<'a, TileAnimations>,
+}
+
+/// Advances the animations of animated tiles in the scene.
+pub struct TileAnimationSystem;
+
+impl<'a> System<'a> for TileAnimationSystem {
+    type SystemData = TileAnimationSystemData<'a>;
+
+    fn run(&mut self, mut data: Self::SystemData) {
+        for animation in &mut data.tile_animations.0 {
+            animation.update(*data.time_since_last_frame);
+        }
+    }
+}
+
+impl TileAnimationSystem {
+    pub fn new() -> Self {
+        TileAnimationSystem {}
+    }
+}